@@ -0,0 +1,107 @@
+//! Bindings of `BonsaiPay`, generated from `contracts/src/IBonsaiPay.sol`.
+//!
+//! Clients decode why a call reverted with `ContractError::decode`, and the contract's logs with
+//! `decode_event`.
+
+use alloy_primitives::{Bytes, B256};
+use alloy_sol_types::{sol, SolEventInterface, SolInterface};
+use thiserror::Error;
+
+sol!(
+    #[sol(all_derives)]
+    "../contracts/src/IBonsaiPay.sol"
+);
+
+pub use IBonsaiPay::{IBonsaiPayErrors, IBonsaiPayEvents};
+
+/// Why a call to `BonsaiPay` reverted.
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub enum ContractError {
+    #[error("invalid deposit: {0}")]
+    InvalidDeposit(String),
+    #[error("invalid claim: {0}")]
+    InvalidClaim(String),
+    #[error("transferring the claimed balance failed")]
+    TransferFailed,
+    /// Revert data that is not one of the contract's errors, such as a failed assertion.
+    #[error("reverted with {0}")]
+    Unknown(Bytes),
+}
+
+impl ContractError {
+    /// Decodes the revert data of a failed call.
+    pub fn decode(data: &[u8]) -> Self {
+        match IBonsaiPayErrors::abi_decode(data, true) {
+            Ok(IBonsaiPayErrors::InvalidDeposit(err)) => Self::InvalidDeposit(err.message),
+            Ok(IBonsaiPayErrors::InvalidClaim(err)) => Self::InvalidClaim(err.message),
+            Ok(IBonsaiPayErrors::TransferFailed(_)) => Self::TransferFailed,
+            Err(_) => Self::Unknown(Bytes::copy_from_slice(data)),
+        }
+    }
+}
+
+/// Decodes a log the contract emitted, or `None` if it is not one of its events.
+pub fn decode_event(topics: &[B256], data: &[u8]) -> Option<IBonsaiPayEvents> {
+    IBonsaiPayEvents::decode_raw_log(topics, data, true).ok()
+}
+
+#[cfg(test)]
+mod test_contract {
+    use alloy_primitives::{Address, Bytes, B256, U256};
+    use alloy_sol_types::{SolError, SolEvent};
+
+    use super::{decode_event, ContractError, IBonsaiPay, IBonsaiPayEvents};
+
+    #[test]
+    fn test_decode_errors() {
+        let invalid = IBonsaiPay::InvalidClaim {
+            message: "No claimable balance".to_string(),
+        };
+        assert_eq!(
+            ContractError::decode(&invalid.abi_encode()),
+            ContractError::InvalidClaim("No claimable balance".to_string())
+        );
+        let invalid = IBonsaiPay::InvalidDeposit {
+            message: "Zero deposit amount".to_string(),
+        };
+        assert_eq!(
+            ContractError::decode(&invalid.abi_encode()),
+            ContractError::InvalidDeposit("Zero deposit amount".to_string())
+        );
+        assert_eq!(
+            ContractError::decode(&IBonsaiPay::TransferFailed {}.abi_encode()),
+            ContractError::TransferFailed
+        );
+        assert_eq!(
+            ContractError::decode(&[0xde, 0xad]),
+            ContractError::Unknown(Bytes::from_static(&[0xde, 0xad]))
+        );
+    }
+
+    #[test]
+    fn test_decode_events() {
+        let claimed = IBonsaiPay::Claimed {
+            recipient: Address::repeat_byte(0x11),
+            claimId: B256::repeat_byte(0xc1),
+            amount: U256::from(1_000),
+        };
+        let log = claimed.encode_log_data();
+        assert_eq!(
+            decode_event(log.topics(), &log.data),
+            Some(IBonsaiPayEvents::Claimed(claimed))
+        );
+
+        let deposited = IBonsaiPay::Deposited {
+            claimId: B256::repeat_byte(0xc1),
+            amount: U256::from(1_000),
+        };
+        let log = deposited.encode_log_data();
+        assert_eq!(
+            decode_event(log.topics(), &log.data),
+            Some(IBonsaiPayEvents::Deposited(deposited))
+        );
+
+        // A log with the signature of `Deposited` but without its indexed claim ID.
+        assert_eq!(decode_event(&log.topics()[..1], &log.data), None);
+    }
+}
@@ -0,0 +1,67 @@
+//! Fixtures to test the verification of claims in Solidity, see `contracts/test`, with the
+//! `fixture` feature.
+
+use std::path::Path;
+
+use alloy_primitives::{Address, B256, U256};
+use alloy_sol_types::SolType;
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use sp1_sdk::{HashableKey, SP1ProofWithPublicValues, SP1VerifyingKey};
+
+use crate::ProofOutputs;
+
+/// A fixture that can be used to test the verification of SP1 zkVM proofs inside Solidity.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProofFixture {
+    pub msg_sender: Address,
+    pub chain_id: u64,
+    pub contract_address: Address,
+    pub claim_id: B256,
+    pub key_digest: B256,
+    pub timestamp: u64,
+    pub policy_hash: B256,
+    pub identity_provider: u32,
+    pub claim_scheme: u8,
+    pub relayer: Address,
+    pub fee: U256,
+    pub nonce: B256,
+    pub vkey: String,
+    pub public_values: String,
+    pub proof: String,
+}
+
+impl ProofFixture {
+    pub fn new(proof: &SP1ProofWithPublicValues, vk: &SP1VerifyingKey) -> Result<Self> {
+        let bytes = proof.public_values.as_slice();
+        let outputs =
+            ProofOutputs::abi_decode(bytes, true).context("decoding the public values")?;
+
+        Ok(Self {
+            msg_sender: outputs.msgSender,
+            chain_id: outputs.chainId,
+            contract_address: outputs.contractAddress,
+            claim_id: outputs.claimId,
+            key_digest: outputs.keyDigest,
+            timestamp: outputs.timestamp,
+            policy_hash: outputs.policyHash,
+            identity_provider: outputs.identityProvider,
+            claim_scheme: outputs.claimScheme,
+            relayer: outputs.relayer,
+            fee: outputs.fee,
+            nonce: outputs.nonce,
+            vkey: vk.bytes32(),
+            public_values: format!("0x{}", hex::encode(bytes)),
+            proof: format!("0x{}", hex::encode(proof.bytes())),
+        })
+    }
+
+    pub fn save(&self, path: &Path) -> Result<()> {
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir)?;
+        }
+        std::fs::write(path, serde_json::to_string_pretty(self)?)
+            .with_context(|| format!("writing {}", path.display()))
+    }
+}
@@ -0,0 +1,150 @@
+//! Fetches the signing keys of an issuer for the host, with the `jwks-client` feature.
+//!
+//! The program receives the keys as the exact bytes the issuer served, so the client never
+//! re-serializes them. Keys fetched over HTTP are cached for as long as the response's
+//! `Cache-Control: max-age` allows.
+
+use std::{
+    path::{Path, PathBuf},
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+
+use alloy_primitives::Bytes;
+use anyhow::{Context, Result};
+use reqwest::header::CACHE_CONTROL;
+
+use crate::JwkKeys;
+
+/// Where Google publishes the keys it signs ID tokens with.
+pub const GOOGLE_JWKS_URL: &str = "https://www.googleapis.com/oauth2/v3/certs";
+
+/// Signing keys as served by the issuer, and parsed.
+pub struct Jwks {
+    /// The bytes passed to the program as `ProofInputs::cert`.
+    pub raw: Bytes,
+    pub keys: JwkKeys,
+}
+
+impl Jwks {
+    pub fn parse(raw: impl Into<Bytes>) -> Result<Self> {
+        let raw = raw.into();
+        let keys = serde_json::from_slice(&raw).context("parsing signing keys")?;
+        Ok(Self { raw, keys })
+    }
+
+    pub fn load(path: &Path) -> Result<Self> {
+        let raw = std::fs::read(path).with_context(|| format!("reading {}", path.display()))?;
+        Self::parse(raw)
+    }
+}
+
+enum JwksSource {
+    Url(String),
+    File(PathBuf),
+}
+
+/// Fetches the keys from a URL, or loads them from a file for offline use.
+pub struct JwksClient {
+    source: JwksSource,
+    http: reqwest::Client,
+    cached: Mutex<Option<(Arc<Jwks>, Option<Instant>)>>,
+}
+
+impl JwksClient {
+    pub fn new(url: impl Into<String>) -> Self {
+        Self::with_source(JwksSource::Url(url.into()))
+    }
+
+    /// Google's keys.
+    pub fn google() -> Self {
+        Self::new(GOOGLE_JWKS_URL)
+    }
+
+    /// Keys saved to `path`, loaded once.
+    pub fn from_file(path: impl Into<PathBuf>) -> Self {
+        Self::with_source(JwksSource::File(path.into()))
+    }
+
+    fn with_source(source: JwksSource) -> Self {
+        Self {
+            source,
+            http: reqwest::Client::new(),
+            cached: Mutex::new(None),
+        }
+    }
+
+    /// The current keys, fetched again once the cached response expires.
+    pub async fn fetch(&self) -> Result<Arc<Jwks>> {
+        if let Some((jwks, expires)) = &*self.cached.lock().unwrap() {
+            if expires.map_or(true, |expires| Instant::now() < expires) {
+                return Ok(jwks.clone());
+            }
+        }
+
+        let (jwks, expires) = match &self.source {
+            JwksSource::File(path) => (Jwks::load(path)?, None),
+            JwksSource::Url(url) => {
+                let response = self.http.get(url).send().await?.error_for_status()?;
+                let lifetime = response
+                    .headers()
+                    .get(CACHE_CONTROL)
+                    .and_then(|value| value.to_str().ok())
+                    .and_then(max_age);
+                let jwks = Jwks::parse(response.bytes().await?)
+                    .with_context(|| format!("fetching {url}"))?;
+                // Responses without a lifetime are fetched again every time.
+                let expires = Instant::now() + lifetime.unwrap_or_default();
+                (jwks, Some(expires))
+            }
+        };
+
+        let jwks = Arc::new(jwks);
+        *self.cached.lock().unwrap() = Some((jwks.clone(), expires));
+        Ok(jwks)
+    }
+}
+
+/// The `max-age` of a `Cache-Control` header, or `None` if the response must not be reused.
+pub fn max_age(cache_control: &str) -> Option<Duration> {
+    let mut max_age = None;
+    for directive in cache_control.split(',') {
+        let directive = directive.trim().to_ascii_lowercase();
+        match directive.split_once('=') {
+            Some(("max-age", seconds)) => max_age = seconds.trim_matches('"').parse().ok(),
+            None if directive == "no-store" || directive == "no-cache" => return None,
+            _ => {}
+        }
+    }
+    max_age.map(Duration::from_secs)
+}
+
+#[cfg(test)]
+mod test_jwks {
+    use std::time::Duration;
+
+    use super::{max_age, Jwks};
+
+    #[test]
+    fn test_max_age() {
+        // As served by Google.
+        assert_eq!(
+            max_age("public, max-age=21600, must-revalidate, no-transform"),
+            Some(Duration::from_secs(21_600))
+        );
+        assert_eq!(max_age("Max-Age=\"60\""), Some(Duration::from_secs(60)));
+        assert_eq!(max_age("public"), None);
+        assert_eq!(max_age("no-store, max-age=60"), None);
+        assert_eq!(max_age("max-age=soon"), None);
+    }
+
+    #[test]
+    fn test_raw_bytes_are_preserved() {
+        let raw = r#"{ "keys": [ { "kty": "OKP", "crv": "Ed25519", "x": "11qYAYKxCrfVS_7TyWQHOg7hcvPapiMlrwIaaPcHURo", "kid": "a" } ] }"#;
+        let jwks = Jwks::parse(raw.as_bytes().to_vec()).unwrap();
+
+        assert_eq!(jwks.raw.as_ref(), raw.as_bytes());
+        assert_eq!(jwks.keys.keys.len(), 1);
+        assert!(jwks.keys.key_digest("a").is_some());
+    }
+}
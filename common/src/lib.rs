@@ -1,2 +1,11 @@
+pub mod contract;
+#[cfg(feature = "fixture")]
+pub mod fixture;
+#[cfg(feature = "jwks-client")]
+pub mod jwks;
+pub mod normalize;
+#[cfg(feature = "preflight")]
+pub mod preflight;
 pub mod types;
+pub use normalize::*;
 pub use types::*;
@@ -0,0 +1,30 @@
+//! Normalization of identifiers before they are hashed into claim IDs.
+//!
+//! Depositors type identifiers by hand while providers return them as stored, so both sides
+//! normalize them the same way. Only ASCII is case-folded, which keeps the result independent of
+//! the Unicode version of whichever tool computes it. The test vectors in
+//! `contracts/src/fixtures/claim_ids.json` are checked against this module and `ClaimIds.sol`.
+
+/// Domains whose mailboxes ignore dots and `+` suffixes in the local part.
+pub const GMAIL_DOMAINS: [&str; 2] = ["gmail.com", "googlemail.com"];
+
+/// Lowercases the ASCII letters of `identifier`.
+pub fn normalize_identifier(identifier: &str) -> String {
+    identifier.to_ascii_lowercase()
+}
+
+/// Normalizes `identifier`, then, for Gmail addresses, removes dots and any `+` suffix from the
+/// local part and replaces `googlemail.com` with `gmail.com`. Other identifiers are only
+/// normalized.
+pub fn fold_gmail_address(identifier: &str) -> String {
+    let normalized = normalize_identifier(identifier);
+    let Some((local, domain)) = normalized.rsplit_once('@') else {
+        return normalized;
+    };
+    if !GMAIL_DOMAINS.contains(&domain) {
+        return normalized;
+    }
+
+    let local = local.split('+').next().unwrap_or_default().replace('.', "");
+    format!("{local}@{}", GMAIL_DOMAINS[0])
+}
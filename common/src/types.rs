@@ -1,22 +1,302 @@
-use alloy_primitives::{U256, Bytes};
-use alloy_sol_types::sol;
+use alloy_primitives::{Address, U256, Bytes, B256};
+use alloy_sol_types::{sol, SolType};
 use serde::{Deserialize, Serialize};
 use jwt_compact::jwk::JsonWebKey;
+use sha2::{Digest, Sha256};
+
+use crate::normalize::{fold_gmail_address, normalize_identifier};
+
+/// Issuers Google uses in ID tokens.
+pub const GOOGLE_ISSUERS: [&str; 2] = ["https://accounts.google.com", "accounts.google.com"];
 
 #[derive(Serialize, Deserialize)]
 pub struct ProofInputs {
     pub identity_provider: U256,
     pub jwt: String,
     pub cert: Bytes,
+    /// UNIX time in seconds against which the token's time claims are checked.
+    pub timestamp: u64,
+    pub policy: ValidationPolicy,
+    /// What the claim ID is derived from, as chosen by the depositor.
+    pub claim_key: ClaimKey,
+    /// Salt of a salted claim, see `ClaimScheme`. Only the salted claim ID is committed.
+    pub salt: Option<B256>,
+    /// Preimage of the token's nonce.
+    pub nonce: ClaimNonce,
+}
+
+/// What the `nonce` of a token binds it to. The nonce is the 0x-prefixed hex of `commitment()`.
+///
+/// The preimage is a private input, so the token cannot be replayed for another recipient, on
+/// another chain or against another deployment, and stops being usable after `expiry`.
+///
+/// A claimant without ETH for gas can have a relayer submit the claim. The relayer and the fee it
+/// deducts from the claimed balance are part of the preimage, so the claimant agrees to them when
+/// signing in and no one else can change them.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct ClaimNonce {
+    /// Address the claimed funds are sent to.
+    pub recipient: Address,
+    pub chain_id: u64,
+    /// Address of the BonsaiPay contract.
+    pub contract: Address,
+    /// UNIX time in seconds after which the nonce is rejected.
+    pub expiry: u64,
+    /// Makes the nonce unique per token request.
+    pub random: B256,
+    /// Address paid `fee`, or zero without a relayer fee.
+    #[serde(default)]
+    pub relayer: Address,
+    /// Wei of the claimed balance paid to `relayer`.
+    #[serde(default)]
+    pub fee: U256,
+}
+
+// address recipient
+// uint64 chainId
+// address contract
+// uint64 expiry
+// bytes32 random
+// address relayer
+// uint256 fee
+type NonceEncoding = sol! {
+    tuple(address, uint64, address, uint64, bytes32, address, uint256)
+};
+
+impl ClaimNonce {
+    /// `sha256(abi.encode(recipient, chainId, contract, expiry, random, relayer, fee))`.
+    pub fn commitment(&self) -> B256 {
+        let encoded = NonceEncoding::abi_encode_params(&(
+            self.recipient,
+            self.chain_id,
+            self.contract,
+            self.expiry,
+            self.random,
+            self.relayer,
+            self.fee,
+        ));
+        B256::from_slice(Sha256::digest(encoded).as_slice())
+    }
+
+    /// The value to request the token with, e.g. Google Sign-In's `nonce` parameter.
+    pub fn nonce(&self) -> String {
+        self.commitment().to_string()
+    }
+}
+
+/// The issuers and OAuth clients whose tokens are accepted.
+///
+/// The policy is a private input to the program; only its hash is committed, so the verifier pins
+/// the trusted OAuth client by comparing `hash()` against a stored value.
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq, Eq)]
+pub struct ValidationPolicy {
+    /// Accepted `iss` values.
+    pub issuers: Vec<String>,
+    /// Accepted `aud` values, i.e. the client IDs tokens must have been minted for.
+    pub audiences: Vec<String>,
+    /// If set, the `azp` claim must be present and equal to this value.
+    pub authorized_party: Option<String>,
+    /// If set, only accounts of this Google Workspace domain (`hd` claim) are accepted.
+    pub hosted_domain: Option<String>,
+    /// Seconds of clock skew tolerated when checking the token's `exp`, `iat` and `nbf` claims
+    /// against the proof's timestamp.
+    pub leeway: u64,
+}
+
+/// Default of `ValidationPolicy::leeway`.
+pub const DEFAULT_LEEWAY: u64 = 300;
+
+// string[] issuers
+// string[] audiences
+// string authorized_party
+// string hosted_domain
+// uint64 leeway
+type PolicyEncoding = sol! {
+    tuple(string[], string[], string, string, uint64)
+};
+
+impl ValidationPolicy {
+    /// Accepts tokens of `issuer` minted for `client_id`.
+    pub fn new(issuer: impl Into<String>, client_id: impl Into<String>) -> Self {
+        Self {
+            issuers: vec![issuer.into()],
+            audiences: vec![client_id.into()],
+            authorized_party: None,
+            hosted_domain: None,
+            leeway: DEFAULT_LEEWAY,
+        }
+    }
+
+    /// Accepts Google ID tokens minted for `client_id` only.
+    pub fn google(client_id: impl Into<String>) -> Self {
+        let client_id = client_id.into();
+        Self {
+            issuers: GOOGLE_ISSUERS.iter().map(|iss| iss.to_string()).collect(),
+            audiences: vec![client_id.clone()],
+            authorized_party: Some(client_id),
+            hosted_domain: None,
+            leeway: DEFAULT_LEEWAY,
+        }
+    }
+
+    /// Restricts the policy to accounts of the Google Workspace `domain`.
+    pub fn with_hosted_domain(mut self, domain: impl Into<String>) -> Self {
+        self.hosted_domain = Some(domain.into());
+        self
+    }
+
+    /// Tolerates `leeway` seconds of clock skew instead of `DEFAULT_LEEWAY`.
+    pub fn with_leeway(mut self, leeway: u64) -> Self {
+        self.leeway = leeway;
+        self
+    }
+
+    /// `sha256(abi.encode(issuers, audiences, authorizedParty, hostedDomain, leeway))`, with
+    /// absent values encoded as the empty string.
+    pub fn hash(&self) -> B256 {
+        let encoded = PolicyEncoding::abi_encode_params(&(
+            self.issuers.clone(),
+            self.audiences.clone(),
+            self.authorized_party.clone().unwrap_or_default(),
+            self.hosted_domain.clone().unwrap_or_default(),
+            self.leeway,
+        ));
+        B256::from_slice(Sha256::digest(encoded).as_slice())
+    }
+}
+
+/// Version of the `ProofOutputs` layout, bumped whenever its fields change.
+pub const PROOF_OUTPUTS_VERSION: u8 = 5;
+
+sol! {
+    /// The public values committed by the program.
+    struct ProofOutputs {
+        /// Layout version, see `PROOF_OUTPUTS_VERSION`.
+        uint8 version;
+        /// The `IdentityProvider` that issued the token.
+        uint32 identityProvider;
+        /// The `ClaimScheme` that derived `claimId` from the token.
+        uint8 claimScheme;
+        address msgSender;
+        /// Chain and contract the token's nonce was bound to, see `ClaimNonce`.
+        uint64 chainId;
+        address contractAddress;
+        bytes32 claimId;
+        /// Digest of the key that verified the token, see `ExtendedJsonWebKey::digest`.
+        bytes32 keyDigest;
+        uint64 timestamp;
+        bytes32 policyHash;
+        /// Relayer paid `fee` wei of the claimed balance, see `ClaimNonce`.
+        address relayer;
+        uint256 fee;
+        /// The token's nonce, `ClaimNonce::commitment`. The contract accepts one claim per nonce.
+        bytes32 nonce;
+    }
+}
+
+/// How a claim ID is derived from the identifier a provider attests to.
+///
+/// Deposits are keyed by claim ID, so depositors and the program must use the same scheme.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[repr(u8)]
+pub enum ClaimScheme {
+    /// `sha256(identifier)`, e.g. the hash of a Google account's email address, exactly as the
+    /// provider returns it.
+    ///
+    /// Anyone can check a guessed identifier against the claim IDs of `Deposited` events.
+    IdentifierSha256 = 0,
+    /// `sha256(salt || identifier)` with a random 32-byte salt the depositor shares with the
+    /// claimant, who passes it to the program as a private input. Without the salt the claim ID
+    /// cannot be linked to the identifier.
+    SaltedIdentifierSha256 = 1,
+    /// `sha256(abi.encode(string iss, string sub))`. Unlike email addresses, subjects do not
+    /// change and are never reassigned to another account.
+    SubjectSha256 = 2,
+    /// `sha256(salt || abi.encode(string iss, string sub))`, salted as `SaltedIdentifierSha256`.
+    SaltedSubjectSha256 = 3,
+    /// `sha256(identifier)` after `fold_gmail_address`, so that any spelling of a Gmail address
+    /// can be deposited to.
+    GmailFoldedIdentifierSha256 = 4,
+    /// `sha256(salt || identifier)` after `fold_gmail_address`.
+    SaltedGmailFoldedIdentifierSha256 = 5,
+    /// `sha256(identifier)` after `normalize_identifier`, so that the email address can be
+    /// deposited to in any case. A separate scheme, so that the claim IDs of `IdentifierSha256`
+    /// deposits do not change.
+    NormalizedIdentifierSha256 = 6,
+    /// `sha256(salt || identifier)` after `normalize_identifier`.
+    SaltedNormalizedIdentifierSha256 = 7,
 }
 
-// address msg_sender
-// bytes32 claim_id
-// bytes jsonwebkey
-pub type ProofOutputs = sol! {
-    tuple(address, bytes32, bytes)
+/// What a claim ID is derived from, selected per deposit.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ClaimKey {
+    /// The identifier the provider attests to, e.g. the email address of a Google account.
+    #[default]
+    Identifier,
+    /// The issuer and subject of the token.
+    Subject,
+    /// The identifier with Gmail addresses folded, see `fold_gmail_address`.
+    GmailFoldedIdentifier,
+    /// The identifier with its ASCII letters lowercased, see `normalize_identifier`.
+    NormalizedIdentifier,
+}
+
+/// The identity a validated token attests to.
+#[derive(Debug, Clone, Copy)]
+pub struct ClaimIdentity<'a> {
+    pub issuer: &'a str,
+    pub subject: &'a str,
+    pub identifier: &'a str,
+}
+
+// string iss
+// string sub
+type SubjectEncoding = sol! {
+    tuple(string, string)
 };
 
+impl ClaimScheme {
+    pub fn id(self) -> u8 {
+        self as u8
+    }
+
+    /// Derives the claim ID of `identity` from `key`, salted if a `salt` is given, and the scheme
+    /// used.
+    pub fn derive(key: ClaimKey, identity: &ClaimIdentity, salt: Option<&B256>) -> (Self, B256) {
+        let mut hasher = Sha256::new();
+        if let Some(salt) = salt {
+            hasher.update(salt);
+        }
+
+        let scheme = match (key, salt.is_some()) {
+            (ClaimKey::Identifier, false) => Self::IdentifierSha256,
+            (ClaimKey::Identifier, true) => Self::SaltedIdentifierSha256,
+            (ClaimKey::Subject, false) => Self::SubjectSha256,
+            (ClaimKey::Subject, true) => Self::SaltedSubjectSha256,
+            (ClaimKey::GmailFoldedIdentifier, false) => Self::GmailFoldedIdentifierSha256,
+            (ClaimKey::GmailFoldedIdentifier, true) => Self::SaltedGmailFoldedIdentifierSha256,
+            (ClaimKey::NormalizedIdentifier, false) => Self::NormalizedIdentifierSha256,
+            (ClaimKey::NormalizedIdentifier, true) => Self::SaltedNormalizedIdentifierSha256,
+        };
+        match key {
+            ClaimKey::Identifier => hasher.update(identity.identifier),
+            ClaimKey::NormalizedIdentifier => {
+                hasher.update(normalize_identifier(identity.identifier))
+            }
+            ClaimKey::GmailFoldedIdentifier => {
+                hasher.update(fold_gmail_address(identity.identifier))
+            }
+            ClaimKey::Subject => hasher.update(SubjectEncoding::abi_encode_params(&(
+                identity.issuer.to_string(),
+                identity.subject.to_string(),
+            ))),
+        }
+
+        (scheme, B256::from_slice(hasher.finalize().as_slice()))
+    }
+}
+
 #[derive(Deserialize, Serialize)]
 pub struct JwkKeys {
     pub keys: Vec<ExtendedJsonWebKey<'static, Extra>>,
@@ -34,4 +314,63 @@ pub struct ExtendedJsonWebKey<'a, T> {
 pub struct Extra {
     #[serde(rename = "kid")]
     pub key_id: String,
-}
\ No newline at end of file
+    /// The algorithm the key is intended for, if the issuer declares one.
+    #[serde(rename = "alg", default, skip_serializing_if = "Option::is_none")]
+    pub algorithm: Option<String>,
+}
+
+// string kid
+// bytes32 thumbprint
+type KeyDigestEncoding = sol! {
+    tuple(string, bytes32)
+};
+
+impl JwkKeys {
+    /// Digest of the key `key_id`, see `ExtendedJsonWebKey::digest`.
+    pub fn key_digest(&self, key_id: &str) -> Option<B256> {
+        self.keys
+            .iter()
+            .find(|key| key.extra.key_id == key_id)
+            .and_then(|key| key.digest())
+    }
+}
+
+impl ExtendedJsonWebKey<'_, Extra> {
+    /// RFC 7638 thumbprint: the SHA-256 of the key's required members, serialized as compact JSON
+    /// in lexicographic order. `None` for key types without a defined thumbprint.
+    pub fn thumbprint(&self) -> Option<B256> {
+        let value = serde_json::to_value(&self.base).ok()?;
+        let members: &[&str] = match value.get("kty")?.as_str()? {
+            "RSA" => &["e", "kty", "n"],
+            "EC" => &["crv", "kty", "x", "y"],
+            "OKP" => &["crv", "kty", "x"],
+            _ => return None,
+        };
+
+        // Written by hand rather than through `serde_json::Map`, whose member order depends on
+        // the `preserve_order` feature.
+        let mut canonical = String::from("{");
+        for (i, member) in members.iter().enumerate() {
+            if i > 0 {
+                canonical.push(',');
+            }
+            canonical.push_str(&serde_json::to_string(member).ok()?);
+            canonical.push(':');
+            canonical.push_str(&serde_json::to_string(value.get(*member)?).ok()?);
+        }
+        canonical.push('}');
+
+        Some(B256::from_slice(
+            Sha256::digest(canonical.as_bytes()).as_slice(),
+        ))
+    }
+
+    /// `sha256(abi.encode(kid, thumbprint))`, the digest under which the key is registered
+    /// on-chain. Unlike the JWKS bytes it does not depend on the other keys of the set, their
+    /// order or the JSON formatting.
+    pub fn digest(&self) -> Option<B256> {
+        let encoded =
+            KeyDigestEncoding::abi_encode_params(&(self.extra.key_id.clone(), self.thumbprint()?));
+        Some(B256::from_slice(Sha256::digest(encoded).as_slice()))
+    }
+}
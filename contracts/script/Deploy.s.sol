@@ -46,7 +46,13 @@ contract BonsaiPayDeploy is Script {
             verifier = ISP1Verifier(address(vm.envAddress("SP1_VERIFIER_ADDRESS")));
         }
         
-        BonsaiPay bonsaiPay = new BonsaiPay(verifier, vm.envBytes32("SP1_PAY_PROGRAM_VKEY"), vm.envBytes("CERT"));
+        BonsaiPay bonsaiPay = new BonsaiPay(
+            verifier,
+            vm.envBytes32("SP1_PAY_PROGRAM_VKEY"),
+            vm.envBytes32("KEY_DIGESTS", ","),
+            uint32(vm.envOr("IDENTITY_PROVIDER", uint256(0))),
+            vm.envBytes32("POLICY_HASH")
+        );
         console2.log("Deployed BonsaiPay to", address(bonsaiPay));
 
         vm.stopBroadcast();
@@ -16,13 +16,26 @@
 pragma solidity ^0.8.20;
 import "forge-std/console.sol";
 import {ISP1Verifier} from "@sp1-contracts/ISP1Verifier.sol";
+import {IBonsaiPay} from "./IBonsaiPay.sol";
 
-contract BonsaiPay {
+contract BonsaiPay is IBonsaiPay {
     ISP1Verifier public immutable verifier;
     bytes32 public immutable bonsaiPayVKey;
-    bytes public cert;
+    // Digests of the JWKs tokens may be signed with, see `ExtendedJsonWebKey::digest`. Issuers
+    // rotate keys, so several are registered at once and revoked once no proof relies on them.
+    mapping(bytes32 => bool) public registeredKeys;
+    bytes32[] private keyDigests;
+    // Hash of the issuer/audience policy the program validated tokens against.
+    bytes32 public policyHash;
+    // Identity provider the program must have validated tokens with, see `IdentityProvider`.
+    uint32 public identityProvider;
     address public owner;
 
+    // Maximum age of the time attested by a proof, covering proving and inclusion latency.
+    uint64 public constant MAX_PROOF_AGE = 1 hours;
+    // Tolerated drift between the prover's clock and the block timestamp.
+    uint64 public constant MAX_CLOCK_DRIFT = 5 minutes;
+
     enum ClaimStatus {
         Pending,
         Claimed
@@ -34,10 +47,25 @@ contract BonsaiPay {
         uint256 amount;
     }
 
-   struct ProofOutputs {
-        address msg_sender;
-        bytes32 claim_id;
-        bytes cert;
+    // Layout version of the public values, see `common::PROOF_OUTPUTS_VERSION`.
+    uint8 public constant PROOF_OUTPUTS_VERSION = 5;
+
+    struct ProofOutputs {
+        uint8 version;
+        uint32 identityProvider;
+        uint8 claimScheme;
+        address msgSender;
+        uint64 chainId;
+        address contractAddress;
+        bytes32 claimId;
+        bytes32 keyDigest;
+        uint64 timestamp;
+        bytes32 policyHash;
+        // Paid `fee` out of the claimed balance for submitting the claim, see `common::ClaimNonce`.
+        address relayer;
+        uint256 fee;
+        // The token's nonce, see `common::ClaimNonce::commitment`.
+        bytes32 nonce;
     }
 
     modifier onlyOwner() {
@@ -47,18 +75,24 @@ contract BonsaiPay {
     
     Deposit[] private deposits;
     mapping(bytes32 => uint256[]) private claimRecords;
-
-    event Deposited(bytes32 indexed claimId, uint256 amount);
-    event Claimed(address indexed recipient, bytes32 indexed claimId, uint256 amount);
-
-    error InvalidDeposit(string message);
-    error InvalidClaim(string message);
-    error TransferFailed();
-
-    constructor(ISP1Verifier _verifier, bytes32 _bonsaiPayVKey, bytes memory _cert) {
+    // Nonces of the tokens of accepted claims, so each token is claimed once, whichever proof of it
+    // is submitted.
+    mapping(bytes32 => bool) public usedNonces;
+
+    constructor(
+        ISP1Verifier _verifier,
+        bytes32 _bonsaiPayVKey,
+        bytes32[] memory _keyDigests,
+        uint32 _identityProvider,
+        bytes32 _policyHash
+    ) {
         verifier = _verifier;
         bonsaiPayVKey = _bonsaiPayVKey;
-        cert = _cert;
+        for (uint256 i = 0; i < _keyDigests.length; ++i) {
+            _registerKey(_keyDigests[i]);
+        }
+        identityProvider = _identityProvider;
+        policyHash = _policyHash;
         owner = msg.sender;
     }   
 
@@ -75,21 +109,40 @@ contract BonsaiPay {
     function claim(bytes calldata proof, bytes calldata publicValues) public {
         ProofOutputs memory po = abi.decode(publicValues, (ProofOutputs));
 
-        if (!(po.cert.length == cert.length && keccak256(po.cert) == keccak256(cert))) revert InvalidClaim("Invalid cert");
-        if (po.msg_sender == address(0)) revert InvalidClaim("Invalid recipient address");
-        if (po.claim_id == bytes32(0)) revert InvalidClaim("Empty claimId");
+        if (po.version != PROOF_OUTPUTS_VERSION) revert InvalidClaim("Unsupported public values version");
+        if (!registeredKeys[po.keyDigest]) revert InvalidClaim("Unregistered key");
+        if (po.identityProvider != identityProvider) revert InvalidClaim("Invalid identity provider");
+        if (po.policyHash != policyHash) revert InvalidClaim("Invalid policy");
+        if (po.chainId != block.chainid) revert InvalidClaim("Invalid chain");
+        if (po.contractAddress != address(this)) revert InvalidClaim("Invalid contract");
+        if (po.msgSender == address(0)) revert InvalidClaim("Invalid recipient address");
+        if (po.claimId == bytes32(0)) revert InvalidClaim("Empty claimId");
+        if (po.fee > 0 && po.relayer == address(0)) revert InvalidClaim("Invalid relayer address");
+        if (po.timestamp > block.timestamp + MAX_CLOCK_DRIFT) revert InvalidClaim("Proof timestamp in the future");
+        if (po.timestamp + MAX_PROOF_AGE < block.timestamp) revert InvalidClaim("Proof expired");
+
+        if (usedNonces[po.nonce]) revert InvalidClaim("Nonce already used");
 
         verifier.verifyProof(bonsaiPayVKey, publicValues, proof);
+        usedNonces[po.nonce] = true;
 
-        uint256[] storage depositIndices = claimRecords[po.claim_id];
+        uint256[] storage depositIndices = claimRecords[po.claimId];
         uint256 balance = _processDeposits(depositIndices);
 
         if (balance == 0) revert InvalidClaim("No claimable balance");
-        (bool success,) = po.msg_sender.call{value: balance}("");
+        if (po.fee > balance) revert InvalidClaim("Fee exceeds balance");
+        uint256 amount = balance - po.fee;
+        (bool success,) = po.msgSender.call{value: amount}("");
 
         if (!success) revert TransferFailed();
 
-        emit Claimed(po.msg_sender, po.claim_id, balance);
+        if (po.fee > 0) {
+            (success,) = po.relayer.call{value: po.fee}("");
+            if (!success) revert TransferFailed();
+            emit RelayerPaid(po.relayer, po.claimId, po.fee);
+        }
+
+        emit Claimed(po.msgSender, po.claimId, amount);
     }
 
     function balanceOf(bytes32 claimId) public view returns (uint256) {
@@ -126,8 +179,42 @@ contract BonsaiPay {
         return balance;
     }
 
-    // Update the JWT certificate - most providers rotate their certificates every so often
-    function updateCert(bytes calldata newCert) public onlyOwner {
-        cert = newCert;
+    function registeredKeyDigests() public view returns (bytes32[] memory) {
+        return keyDigests;
+    }
+
+    // Register a signing key - most providers rotate their keys every so often
+    function registerKey(bytes32 keyDigest) public onlyOwner {
+        _registerKey(keyDigest);
+    }
+
+    // Revoke a signing key, e.g. once the provider stopped publishing it and pending proofs expired
+    function revokeKey(bytes32 keyDigest) public onlyOwner {
+        if (!registeredKeys[keyDigest]) return;
+        registeredKeys[keyDigest] = false;
+
+        for (uint256 i = 0; i < keyDigests.length; ++i) {
+            if (keyDigests[i] == keyDigest) {
+                keyDigests[i] = keyDigests[keyDigests.length - 1];
+                keyDigests.pop();
+                break;
+            }
+        }
+
+        emit KeyRevoked(keyDigest);
+    }
+
+    function _registerKey(bytes32 keyDigest) private {
+        if (registeredKeys[keyDigest]) return;
+        registeredKeys[keyDigest] = true;
+        keyDigests.push(keyDigest);
+
+        emit KeyRegistered(keyDigest);
+    }
+
+    // Update the identity provider and its accepted issuers/OAuth clients, e.g. when moving to a new client ID
+    function updatePolicy(uint32 newIdentityProvider, bytes32 newPolicyHash) public onlyOwner {
+        identityProvider = newIdentityProvider;
+        policyHash = newPolicyHash;
     }
 }
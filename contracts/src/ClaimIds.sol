@@ -0,0 +1,85 @@
+// Copyright 2024 RISC Zero, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+// SPDX-License-Identifier: Apache-2.0
+pragma solidity ^0.8.20;
+
+// Claim IDs of identifiers, matching `ClaimScheme` and the normalization in `common`.
+library ClaimIds {
+    // Claim ID of `IdentifierSha256`
+    function identifierClaimId(string memory identifier) internal pure returns (bytes32) {
+        return sha256(bytes(identifier));
+    }
+
+    // Claim ID of `NormalizedIdentifierSha256`
+    function normalizedClaimId(string memory identifier) internal pure returns (bytes32) {
+        return sha256(bytes(normalizeIdentifier(identifier)));
+    }
+
+    // Claim ID of `GmailFoldedIdentifierSha256`
+    function gmailFoldedClaimId(string memory identifier) internal pure returns (bytes32) {
+        return sha256(bytes(foldGmailAddress(identifier)));
+    }
+
+    // Lowercases the ASCII letters of `identifier`
+    function normalizeIdentifier(string memory identifier) internal pure returns (string memory) {
+        bytes memory input = bytes(identifier);
+        bytes memory output = new bytes(input.length);
+
+        for (uint256 i = 0; i < input.length; ++i) {
+            bytes1 c = input[i];
+            if (c >= "A" && c <= "Z") {
+                c = bytes1(uint8(c) + 32);
+            }
+            output[i] = c;
+        }
+
+        return string(output);
+    }
+
+    // Normalizes `identifier`, then removes dots and `+` suffixes from Gmail addresses
+    function foldGmailAddress(string memory identifier) internal pure returns (string memory) {
+        bytes memory normalized = bytes(normalizeIdentifier(identifier));
+
+        uint256 at = normalized.length;
+        for (uint256 i = normalized.length; i > 0; --i) {
+            if (normalized[i - 1] == "@") {
+                at = i - 1;
+                break;
+            }
+        }
+        if (at == normalized.length) return string(normalized);
+
+        bytes memory domain = new bytes(normalized.length - at - 1);
+        for (uint256 i = 0; i < domain.length; ++i) {
+            domain[i] = normalized[at + 1 + i];
+        }
+        if (keccak256(domain) != keccak256("gmail.com") && keccak256(domain) != keccak256("googlemail.com")) {
+            return string(normalized);
+        }
+
+        bytes memory local = new bytes(at);
+        uint256 length = 0;
+        for (uint256 i = 0; i < at; ++i) {
+            if (normalized[i] == "+") break;
+            if (normalized[i] == ".") continue;
+            local[length++] = normalized[i];
+        }
+        assembly {
+            mstore(local, length)
+        }
+
+        return string.concat(string(local), "@gmail.com");
+    }
+}
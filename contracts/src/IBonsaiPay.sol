@@ -0,0 +1,56 @@
+// Copyright 2024 RISC Zero, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+// SPDX-License-Identifier: Apache-2.0
+pragma solidity ^0.8.20;
+
+// External interface of `BonsaiPay`. The Rust bindings in `common::contract` are generated from
+// this file, so it must not import anything.
+interface IBonsaiPay {
+    event Deposited(bytes32 indexed claimId, uint256 amount);
+    event Claimed(address indexed recipient, bytes32 indexed claimId, uint256 amount);
+    event RelayerPaid(address indexed relayer, bytes32 indexed claimId, uint256 fee);
+    event KeyRegistered(bytes32 indexed keyDigest);
+    event KeyRevoked(bytes32 indexed keyDigest);
+
+    error InvalidDeposit(string message);
+    error InvalidClaim(string message);
+    error TransferFailed();
+
+    function deposit(bytes32 claimId) external payable;
+
+    function claim(bytes calldata proof, bytes calldata publicValues) external;
+
+    function balanceOf(bytes32 claimId) external view returns (uint256);
+
+    function usedNonces(bytes32 nonce) external view returns (bool);
+
+    function bonsaiPayVKey() external view returns (bytes32);
+
+    function registeredKeys(bytes32 keyDigest) external view returns (bool);
+
+    function registeredKeyDigests() external view returns (bytes32[] memory);
+
+    function policyHash() external view returns (bytes32);
+
+    function identityProvider() external view returns (uint32);
+
+    function owner() external view returns (address);
+
+    function registerKey(bytes32 keyDigest) external;
+
+    function revokeKey(bytes32 keyDigest) external;
+
+    function updatePolicy(uint32 newIdentityProvider, bytes32 newPolicyHash) external;
+}
@@ -14,11 +14,19 @@ contract BonsaiPayTest is Test {
 
     struct Fixture {
         address msgSender;
+        uint64 chainId;
+        address contractAddress;
         bytes32 claimId;
+        uint64 timestamp;
+        bytes32 policyHash;
+        uint32 identityProvider;
         bytes32 vkey;
         bytes publicValues;
         bytes proof;
-        bytes cert;
+        bytes32 keyDigest;
+        address relayer;
+        uint256 fee;
+        bytes32 nonce;
     }
 
     Fixture public fixture;
@@ -33,17 +41,39 @@ contract BonsaiPayTest is Test {
 
         // Parse and decode individual fields
         fixture.msgSender = abi.decode(vm.parseJson(fixtureJson, ".msgSender"), (address));
+        fixture.chainId = uint64(vm.parseJsonUint(fixtureJson, ".chainId"));
+        fixture.contractAddress = abi.decode(vm.parseJson(fixtureJson, ".contractAddress"), (address));
         fixture.claimId = abi.decode(vm.parseJson(fixtureJson, ".claimId"), (bytes32));
+        fixture.timestamp = uint64(vm.parseJsonUint(fixtureJson, ".timestamp"));
+        fixture.policyHash = abi.decode(vm.parseJson(fixtureJson, ".policyHash"), (bytes32));
+        fixture.identityProvider = uint32(vm.parseJsonUint(fixtureJson, ".identityProvider"));
         fixture.vkey = vm.envBytes32("SP1_PAY_PROGRAM_VKEY");
         fixture.publicValues = abi.decode(vm.parseJson(fixtureJson, ".publicValues"), (bytes));
         fixture.proof = abi.decode(vm.parseJson(fixtureJson, ".proof"), (bytes));
-        fixture.cert = vm.envBytes("CERT");
+        fixture.keyDigest = abi.decode(vm.parseJson(fixtureJson, ".keyDigest"), (bytes32));
+        fixture.relayer = abi.decode(vm.parseJson(fixtureJson, ".relayer"), (address));
+        fixture.fee = vm.parseJsonUint(fixtureJson, ".fee");
+        fixture.nonce = abi.decode(vm.parseJson(fixtureJson, ".nonce"), (bytes32));
 
         // Deploy BonsaiPay
-        bonsaiPay = new BonsaiPay(ISP1Verifier(address(mockVerifier)), fixture.vkey, fixture.cert);
+        bytes32[] memory keyDigests = new bytes32[](1);
+        keyDigests[0] = fixture.keyDigest;
+        // Deploy to the chain and address the proof's nonce is bound to
+        vm.chainId(fixture.chainId);
+        deployCodeTo(
+            "BonsaiPay.sol:BonsaiPay",
+            abi.encode(
+                ISP1Verifier(address(mockVerifier)), fixture.vkey, keyDigests, fixture.identityProvider, fixture.policyHash
+            ),
+            fixture.contractAddress
+        );
+        bonsaiPay = BonsaiPay(fixture.contractAddress);
 
         // Fund Alice
         vm.deal(ALICE, 10 ether);
+
+        // Claim at the time attested by the proof
+        vm.warp(fixture.timestamp);
     }
 
     function testDeposit() public {
@@ -65,8 +95,56 @@ contract BonsaiPayTest is Test {
         // Perform claim
         bonsaiPay.claim(fixture.proof, fixture.publicValues);
 
-        // Check if the recipient received the funds
-        assertEq(recipient.balance, initialBalance + DEPOSIT_AMOUNT);
+        // Check if the recipient received the funds, less the relayer's fee
+        assertEq(recipient.balance, initialBalance + DEPOSIT_AMOUNT - fixture.fee);
+    }
+
+    function testReplayedClaim() public {
+        vm.prank(ALICE);
+        bonsaiPay.deposit{value: DEPOSIT_AMOUNT}(fixture.claimId);
+        bonsaiPay.claim(fixture.proof, fixture.publicValues);
+        assertTrue(bonsaiPay.usedNonces(fixture.nonce));
+
+        // A later deposit to the same claim ID is not claimable with the same proof
+        vm.prank(ALICE);
+        bonsaiPay.deposit{value: DEPOSIT_AMOUNT}(fixture.claimId);
+        vm.expectRevert(abi.encodeWithSignature("InvalidClaim(string)", "Nonce already used"));
+        bonsaiPay.claim(fixture.proof, fixture.publicValues);
+
+        // Nor with another proof of the same token, made later
+        vm.warp(fixture.timestamp + 60);
+        vm.expectRevert(abi.encodeWithSignature("InvalidClaim(string)", "Nonce already used"));
+        bonsaiPay.claim(fixture.proof, publicValuesAt(fixture.timestamp + 60));
+        assertEq(bonsaiPay.balanceOf(fixture.claimId), DEPOSIT_AMOUNT);
+    }
+
+    function testClaimPaysRelayerFee() public {
+        vm.prank(ALICE);
+        bonsaiPay.deposit{value: DEPOSIT_AMOUNT}(fixture.claimId);
+
+        address relayer = address(0xBEEF);
+        uint256 fee = 0.01 ether;
+        uint256 initialBalance = fixture.msgSender.balance;
+        bonsaiPay.claim(fixture.proof, publicValuesWithFee(relayer, fee));
+
+        assertEq(fixture.msgSender.balance, initialBalance + DEPOSIT_AMOUNT - fee);
+        assertEq(relayer.balance, fee);
+    }
+
+    function testClaimWithFeeAboveBalance() public {
+        vm.prank(ALICE);
+        bonsaiPay.deposit{value: DEPOSIT_AMOUNT}(fixture.claimId);
+
+        vm.expectRevert(abi.encodeWithSignature("InvalidClaim(string)", "Fee exceeds balance"));
+        bonsaiPay.claim(fixture.proof, publicValuesWithFee(address(0xBEEF), DEPOSIT_AMOUNT + 1));
+    }
+
+    function testClaimWithFeeWithoutRelayer() public {
+        vm.prank(ALICE);
+        bonsaiPay.deposit{value: DEPOSIT_AMOUNT}(fixture.claimId);
+
+        vm.expectRevert(abi.encodeWithSignature("InvalidClaim(string)", "Invalid relayer address"));
+        bonsaiPay.claim(fixture.proof, publicValuesWithFee(address(0), 0.01 ether));
     }
 
     function testInvalidClaim() public {
@@ -74,4 +152,88 @@ contract BonsaiPayTest is Test {
         vm.expectRevert(abi.encodeWithSignature("InvalidClaim(string)", "No claimable balance"));
         bonsaiPay.claim(fixture.proof, fixture.publicValues);
     }
-}
\ No newline at end of file
+
+    function testExpiredClaim() public {
+        vm.prank(ALICE);
+        bonsaiPay.deposit{value: DEPOSIT_AMOUNT}(fixture.claimId);
+
+        vm.warp(fixture.timestamp + bonsaiPay.MAX_PROOF_AGE() + 1);
+        vm.expectRevert(abi.encodeWithSignature("InvalidClaim(string)", "Proof expired"));
+        bonsaiPay.claim(fixture.proof, fixture.publicValues);
+    }
+
+    function testClaimWithForeignPolicy() public {
+        vm.prank(ALICE);
+        bonsaiPay.deposit{value: DEPOSIT_AMOUNT}(fixture.claimId);
+
+        bonsaiPay.updatePolicy(fixture.identityProvider, keccak256("another client"));
+        vm.expectRevert(abi.encodeWithSignature("InvalidClaim(string)", "Invalid policy"));
+        bonsaiPay.claim(fixture.proof, fixture.publicValues);
+    }
+
+    function testClaimWithForeignIdentityProvider() public {
+        vm.prank(ALICE);
+        bonsaiPay.deposit{value: DEPOSIT_AMOUNT}(fixture.claimId);
+
+        bonsaiPay.updatePolicy(fixture.identityProvider + 1, fixture.policyHash);
+        vm.expectRevert(abi.encodeWithSignature("InvalidClaim(string)", "Invalid identity provider"));
+        bonsaiPay.claim(fixture.proof, fixture.publicValues);
+    }
+
+    function testClaimAfterKeyRotation() public {
+        vm.prank(ALICE);
+        bonsaiPay.deposit{value: DEPOSIT_AMOUNT}(fixture.claimId);
+
+        // The key of the proof is still registered next to the new one
+        bonsaiPay.registerKey(keccak256("new key"));
+        uint256 initialBalance = fixture.msgSender.balance;
+        bonsaiPay.claim(fixture.proof, fixture.publicValues);
+
+        assertEq(fixture.msgSender.balance, initialBalance + DEPOSIT_AMOUNT - fixture.fee);
+    }
+
+    function testClaimWithRevokedKey() public {
+        vm.prank(ALICE);
+        bonsaiPay.deposit{value: DEPOSIT_AMOUNT}(fixture.claimId);
+
+        bonsaiPay.registerKey(keccak256("new key"));
+        bonsaiPay.revokeKey(fixture.keyDigest);
+        vm.expectRevert(abi.encodeWithSignature("InvalidClaim(string)", "Unregistered key"));
+        bonsaiPay.claim(fixture.proof, fixture.publicValues);
+    }
+
+    function testRegisteredKeyDigests() public {
+        bonsaiPay.registerKey(keccak256("new key"));
+        bonsaiPay.registerKey(keccak256("new key"));
+        assertEq(bonsaiPay.registeredKeyDigests().length, 2);
+
+        bonsaiPay.revokeKey(fixture.keyDigest);
+        bytes32[] memory keyDigests = bonsaiPay.registeredKeyDigests();
+        assertEq(keyDigests.length, 1);
+        assertEq(keyDigests[0], keccak256("new key"));
+    }
+
+    function testClaimOnForeignChain() public {
+        vm.prank(ALICE);
+        bonsaiPay.deposit{value: DEPOSIT_AMOUNT}(fixture.claimId);
+
+        vm.chainId(fixture.chainId + 1);
+        vm.expectRevert(abi.encodeWithSignature("InvalidClaim(string)", "Invalid chain"));
+        bonsaiPay.claim(fixture.proof, fixture.publicValues);
+    }
+
+    // The fixture's public values with another relayer fee. The mock verifier accepts them.
+    function publicValuesWithFee(address relayer, uint256 fee) internal view returns (bytes memory) {
+        BonsaiPay.ProofOutputs memory po = abi.decode(fixture.publicValues, (BonsaiPay.ProofOutputs));
+        po.relayer = relayer;
+        po.fee = fee;
+        return abi.encode(po);
+    }
+
+    // The fixture's public values as proved at `timestamp`. The mock verifier accepts them.
+    function publicValuesAt(uint64 timestamp) internal view returns (bytes memory) {
+        BonsaiPay.ProofOutputs memory po = abi.decode(fixture.publicValues, (BonsaiPay.ProofOutputs));
+        po.timestamp = timestamp;
+        return abi.encode(po);
+    }
+}
@@ -0,0 +1,32 @@
+// SPDX-License-Identifier: Apache-2.0
+pragma solidity ^0.8.20;
+
+import "forge-std/Test.sol";
+import "../src/ClaimIds.sol";
+
+contract ClaimIdsTest is Test {
+    // Fields in the alphabetical order `vm.parseJson` decodes objects in
+    struct Vector {
+        bytes32 claimId;
+        string gmailFolded;
+        bytes32 gmailFoldedClaimId;
+        string identifier;
+        string normalized;
+        bytes32 normalizedClaimId;
+    }
+
+    function testSharedVectors() public {
+        string memory json = vm.readFile("src/fixtures/claim_ids.json");
+        Vector[] memory vectors = abi.decode(vm.parseJson(json, ".vectors"), (Vector[]));
+        assertGt(vectors.length, 0);
+
+        for (uint256 i = 0; i < vectors.length; ++i) {
+            Vector memory v = vectors[i];
+            assertEq(ClaimIds.normalizeIdentifier(v.identifier), v.normalized);
+            assertEq(ClaimIds.foldGmailAddress(v.identifier), v.gmailFolded);
+            assertEq(ClaimIds.identifierClaimId(v.identifier), v.claimId);
+            assertEq(ClaimIds.normalizedClaimId(v.identifier), v.normalizedClaimId);
+            assertEq(ClaimIds.gmailFoldedClaimId(v.identifier), v.gmailFoldedClaimId);
+        }
+    }
+}
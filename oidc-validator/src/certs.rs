@@ -1,3 +1,4 @@
+#[cfg(any(test, feature = "test-provider"))]
 pub static TEST_PUB_JWK: &str = r#"
 {
   "keys" : [
@@ -12,3 +13,61 @@ pub static TEST_PUB_JWK: &str = r#"
   ]
 }
 "#;
+
+/// Keys for the JWS algorithm test vectors: an RSA key without and with a declared `alg`, a P-256
+/// key and an Ed25519 key.
+#[cfg(test)]
+pub static ALGORITHMS_TEST_PUB_JWK: &str = r#"
+{
+  "keys": [
+    {
+      "kty": "RSA",
+      "n": "xj9XOclXMRTj8TgF77ANG39hRCO2JXd4Bpxp9OyKwTYVEcwelY_X9MIg2MZ6tlR2ZveOh9lpNPM9unFq5HodniRmAy8i2GorjPDkBt7PadqH-6o3NLarYsVh4q-dwdktwFpgXCODCtJJ4JRRvJI0b134AWgUEZS4FgZDA3PqRZjR9pX_761DLoqck0VSUGDBN0CerP07x9AqCoRsr6MHoBY_yi1AXR8KNEtmGRl-IM5_11R9Dc4_JcUI7be9WLeh7GgwDPf33RNtzGE9vCaQOMcs52hE2kMIKk_CSSuYOX1VmELH_cFtwCf_zhsCfQBbczaxP9xU8-zdxWLiSdvAbQ",
+      "e": "AQAB",
+      "kid": "rsa-key",
+      "use": "sig"
+    },
+    {
+      "kty": "RSA",
+      "n": "xj9XOclXMRTj8TgF77ANG39hRCO2JXd4Bpxp9OyKwTYVEcwelY_X9MIg2MZ6tlR2ZveOh9lpNPM9unFq5HodniRmAy8i2GorjPDkBt7PadqH-6o3NLarYsVh4q-dwdktwFpgXCODCtJJ4JRRvJI0b134AWgUEZS4FgZDA3PqRZjR9pX_761DLoqck0VSUGDBN0CerP07x9AqCoRsr6MHoBY_yi1AXR8KNEtmGRl-IM5_11R9Dc4_JcUI7be9WLeh7GgwDPf33RNtzGE9vCaQOMcs52hE2kMIKk_CSSuYOX1VmELH_cFtwCf_zhsCfQBbczaxP9xU8-zdxWLiSdvAbQ",
+      "e": "AQAB",
+      "kid": "rs256-key",
+      "alg": "RS256",
+      "use": "sig"
+    },
+    {
+      "kty": "EC",
+      "crv": "P-256",
+      "x": "6H6lL9_Bj8PYtciiJcHRQFeW1yiab3VQXF5K7WJqv9Y",
+      "y": "mcNfEnKIou4jDENIQRq1bv-A5LzreuNdrU2VoiLQqJ4",
+      "kid": "ec-key",
+      "alg": "ES256",
+      "use": "sig"
+    },
+    {
+      "kty": "OKP",
+      "crv": "Ed25519",
+      "x": "gIDSarTsuLyCfBY-OP4tt3sI-l_KphXErbfh6N6frDo",
+      "kid": "ed-key",
+      "alg": "EdDSA",
+      "use": "sig"
+    }
+  ]
+}
+"#;
+
+/// The example key of RFC 7638, section 3.1, whose thumbprint is given there.
+#[cfg(test)]
+pub static THUMBPRINT_TEST_PUB_JWK: &str = r#"
+{
+  "keys": [
+    {
+      "kty": "RSA",
+      "n": "0vx7agoebGcQSuuPiLJXZptN9nndrQmbXEps2aiAFbWhM78LhWx4cbbfAAtVT86zwu1RK7aPFFxuhDR1L6tSoc_BJECPebWKRXjBZCiFV4n3oknjhMstn64tZ_2W-5JsGY4Hc5n9yBXArwl93lqt7_RN5w6Cf0h4QyQ5v-65YGjQR0_FDW2QvzqY368QQMicAtaSqzs8KJZgnYb9c7d0zgdAZHzu6qMQvRL5hajrn1n91CbOpbISD08qNLyrdkt-bFTWhAI4vMQFh6WeZu0fM4lFd2NcRwr3XPksINHaQ-G_xBniIqbw0Ls1jF44-csFCur-kEgU8awapJzKnqDKgw",
+      "e": "AQAB",
+      "alg": "RS256",
+      "kid": "2011-04-29"
+    }
+  ]
+}
+"#;
@@ -0,0 +1,55 @@
+use alloy_primitives::B256;
+use alloy_sol_types::SolType;
+use common::{
+    ClaimIdentity, ClaimKey, ClaimScheme, JwkKeys, ProofInputs, ProofOutputs, PROOF_OUTPUTS_VERSION,
+};
+
+use crate::{IdentityProvider, OidcErr, TimeOptions};
+
+/// Validates the token in `inputs` and derives the ABI-encoded public values of a claim.
+///
+/// This is the whole logic of the program. It is exposed so hosts can replay a rejected execution
+/// natively and learn which check failed.
+pub fn verify_claim(inputs: &ProofInputs) -> Result<Vec<u8>, OidcErr> {
+    let identity_provider = IdentityProvider::try_from(inputs.identity_provider)?;
+    let jwk_keys: JwkKeys =
+        serde_json::from_slice(inputs.cert.as_ref()).map_err(|_| OidcErr::JwksParseError)?;
+    let time = TimeOptions::new(inputs.timestamp).with_leeway(inputs.policy.leeway);
+
+    let validated = identity_provider.validate(&inputs.jwt, &jwk_keys, &inputs.policy, &time)?;
+
+    if inputs.claim_key == ClaimKey::Subject && validated.subject.is_empty() {
+        return Err(OidcErr::SubjectMissingError);
+    }
+    let identity = ClaimIdentity {
+        issuer: &validated.issuer,
+        subject: &validated.subject,
+        identifier: &validated.identifier,
+    };
+    let (claim_scheme, claim_id) =
+        ClaimScheme::derive(inputs.claim_key, &identity, inputs.salt.as_ref());
+
+    let nonce: B256 = validated.nonce.parse().map_err(|_| OidcErr::NonceFormatError)?;
+    if inputs.nonce.commitment() != nonce {
+        return Err(OidcErr::NonceMismatchError);
+    }
+    if inputs.nonce.expiry < inputs.timestamp {
+        return Err(OidcErr::NonceExpiredError);
+    }
+
+    Ok(ProofOutputs::abi_encode(&ProofOutputs {
+        version: PROOF_OUTPUTS_VERSION,
+        identityProvider: identity_provider.id(),
+        claimScheme: claim_scheme.id(),
+        msgSender: inputs.nonce.recipient,
+        chainId: inputs.nonce.chain_id,
+        contractAddress: inputs.nonce.contract,
+        claimId: claim_id,
+        keyDigest: validated.key_digest,
+        timestamp: inputs.timestamp,
+        policyHash: inputs.policy.hash(),
+        relayer: inputs.nonce.relayer,
+        fee: inputs.nonce.fee,
+        nonce,
+    }))
+}
@@ -0,0 +1,66 @@
+use serde::{Deserialize, Deserializer, Serialize};
+
+use super::{verified_email, Provider};
+use crate::OidcErr;
+
+/// Sign in with Apple.
+pub struct Apple;
+
+pub const APPLE_ISSUER: &str = "https://appleid.apple.com";
+
+#[derive(Deserialize, Serialize, Debug, Clone)]
+pub struct AppleClaims {
+    pub aud: String,
+    pub iss: String,
+    pub sub: String,
+    pub nonce: Option<String>,
+    pub email: Option<String>,
+    #[serde(default, deserialize_with = "bool_or_string")]
+    pub email_verified: Option<bool>,
+    #[serde(default, deserialize_with = "bool_or_string")]
+    pub is_private_email: Option<bool>,
+    pub nonce_supported: Option<bool>,
+}
+
+impl Provider for Apple {
+    type Claims = AppleClaims;
+
+    fn issuer(claims: &AppleClaims) -> &str {
+        &claims.iss
+    }
+
+    fn audiences(claims: &AppleClaims) -> Vec<&str> {
+        vec![&claims.aud]
+    }
+
+    fn subject(claims: &AppleClaims) -> &str {
+        &claims.sub
+    }
+
+    fn identifier(claims: &AppleClaims) -> Result<String, OidcErr> {
+        verified_email(claims.email.as_ref(), claims.email_verified)
+    }
+
+    fn nonce(claims: &AppleClaims) -> Result<String, OidcErr> {
+        claims.nonce.clone().ok_or(OidcErr::NonceMissingError)
+    }
+}
+
+/// Apple encodes its boolean claims either as JSON booleans or as `"true"`/`"false"` strings.
+fn bool_or_string<'de, D>(deserializer: D) -> Result<Option<bool>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum BoolOrString {
+        Bool(bool),
+        String(String),
+    }
+
+    Ok(match Option::<BoolOrString>::deserialize(deserializer)? {
+        Some(BoolOrString::Bool(value)) => Some(value),
+        Some(BoolOrString::String(value)) => Some(value == "true"),
+        None => None,
+    })
+}
@@ -0,0 +1,39 @@
+use super::{Generic, Provider, StandardClaims};
+use crate::OidcErr;
+
+/// Auth0 tenants. Tokens follow OpenID Connect Core, so validation is that of [`Generic`]; the
+/// provider only differs in the ID committed with the claim.
+pub struct Auth0;
+
+/// Issuer of ID tokens for the Auth0 tenant at `domain`, e.g. `example.eu.auth0.com`.
+pub fn auth0_issuer(domain: &str) -> String {
+    format!("https://{domain}/")
+}
+
+impl Provider for Auth0 {
+    type Claims = StandardClaims;
+
+    fn issuer(claims: &StandardClaims) -> &str {
+        Generic::issuer(claims)
+    }
+
+    fn audiences(claims: &StandardClaims) -> Vec<&str> {
+        Generic::audiences(claims)
+    }
+
+    fn authorized_party(claims: &StandardClaims) -> Option<&str> {
+        Generic::authorized_party(claims)
+    }
+
+    fn subject(claims: &StandardClaims) -> &str {
+        Generic::subject(claims)
+    }
+
+    fn identifier(claims: &StandardClaims) -> Result<String, OidcErr> {
+        Generic::identifier(claims)
+    }
+
+    fn nonce(claims: &StandardClaims) -> Result<String, OidcErr> {
+        Generic::nonce(claims)
+    }
+}
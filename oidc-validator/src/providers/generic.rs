@@ -0,0 +1,48 @@
+use serde::{Deserialize, Serialize};
+
+use super::{verified_email, Audience, Provider};
+use crate::OidcErr;
+
+/// Any OpenID Connect provider that asserts a verified `email`. The trusted issuers are taken
+/// entirely from the validation policy.
+pub struct Generic;
+
+/// The claims defined by OpenID Connect Core that claims rely on.
+#[derive(Deserialize, Serialize, Debug, Clone)]
+pub struct StandardClaims {
+    pub aud: Audience,
+    pub iss: String,
+    pub sub: String,
+    pub azp: Option<String>,
+    pub nonce: Option<String>,
+    pub email: Option<String>,
+    pub email_verified: Option<bool>,
+}
+
+impl Provider for Generic {
+    type Claims = StandardClaims;
+
+    fn issuer(claims: &StandardClaims) -> &str {
+        &claims.iss
+    }
+
+    fn audiences(claims: &StandardClaims) -> Vec<&str> {
+        claims.aud.to_vec()
+    }
+
+    fn authorized_party(claims: &StandardClaims) -> Option<&str> {
+        claims.azp.as_deref()
+    }
+
+    fn subject(claims: &StandardClaims) -> &str {
+        &claims.sub
+    }
+
+    fn identifier(claims: &StandardClaims) -> Result<String, OidcErr> {
+        verified_email(claims.email.as_ref(), claims.email_verified)
+    }
+
+    fn nonce(claims: &StandardClaims) -> Result<String, OidcErr> {
+        claims.nonce.clone().ok_or(OidcErr::NonceMissingError)
+    }
+}
@@ -0,0 +1,63 @@
+use serde::{Deserialize, Serialize};
+
+use super::Provider;
+use crate::OidcErr;
+
+/// GitHub Actions workflow identity tokens.
+///
+/// These tokens have no `nonce` claim; the only value a workflow controls is the audience. The
+/// nonce is therefore appended to it as `<audience>#<nonce>`, and only `<audience>` is checked
+/// against the policy. Claims are keyed on `sub`, e.g. `repo:owner/name:ref:refs/heads/main`.
+pub struct GitHubActions;
+
+pub const GITHUB_ACTIONS_ISSUER: &str = "https://token.actions.githubusercontent.com";
+
+#[derive(Deserialize, Serialize, Debug, Clone)]
+pub struct GitHubActionsClaims {
+    pub aud: String,
+    pub iss: String,
+    pub sub: String,
+    pub repository: Option<String>,
+    pub repository_owner: Option<String>,
+    pub actor: Option<String>,
+    #[serde(rename = "ref")]
+    pub git_ref: Option<String>,
+    pub workflow_ref: Option<String>,
+    pub run_id: Option<String>,
+}
+
+impl GitHubActionsClaims {
+    fn split_audience(&self) -> Option<(&str, &str)> {
+        self.aud.rsplit_once('#')
+    }
+}
+
+impl Provider for GitHubActions {
+    type Claims = GitHubActionsClaims;
+
+    fn issuer(claims: &GitHubActionsClaims) -> &str {
+        &claims.iss
+    }
+
+    fn audiences(claims: &GitHubActionsClaims) -> Vec<&str> {
+        match claims.split_audience() {
+            Some((audience, _)) => vec![audience],
+            None => vec![&claims.aud],
+        }
+    }
+
+    fn subject(claims: &GitHubActionsClaims) -> &str {
+        &claims.sub
+    }
+
+    fn identifier(claims: &GitHubActionsClaims) -> Result<String, OidcErr> {
+        Ok(claims.sub.clone())
+    }
+
+    fn nonce(claims: &GitHubActionsClaims) -> Result<String, OidcErr> {
+        claims
+            .split_audience()
+            .map(|(_, nonce)| nonce.to_string())
+            .ok_or(OidcErr::NonceMissingError)
+    }
+}
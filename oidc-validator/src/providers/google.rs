@@ -0,0 +1,81 @@
+use common::{ValidationPolicy, GOOGLE_ISSUERS};
+use serde::{Deserialize, Serialize};
+
+use super::Provider;
+use crate::OidcErr;
+
+/// Google Sign-In.
+pub struct Google;
+
+#[derive(Deserialize, Serialize, Debug, Clone)]
+pub struct GoogleClaims {
+    pub aud: String,
+    pub iss: String,
+    pub sub: String,
+    pub nonce: String, // I require this one.
+    pub email: String, // And this one too.
+    pub at_hash: Option<String>,
+    pub azp: Option<String>,
+    pub email_verified: Option<bool>,
+    pub family_name: Option<String>,
+    pub given_name: Option<String>,
+    pub hd: Option<String>,
+    pub locale: Option<String>,
+    pub name: Option<String>,
+    pub picture: Option<String>,
+    pub jti: Option<String>,
+}
+
+impl Provider for Google {
+    type Claims = GoogleClaims;
+
+    fn issuer(claims: &GoogleClaims) -> &str {
+        &claims.iss
+    }
+
+    fn audiences(claims: &GoogleClaims) -> Vec<&str> {
+        vec![&claims.aud]
+    }
+
+    fn authorized_party(claims: &GoogleClaims) -> Option<&str> {
+        claims.azp.as_deref()
+    }
+
+    fn subject(claims: &GoogleClaims) -> &str {
+        &claims.sub
+    }
+
+    fn subject_issuer(_claims: &GoogleClaims) -> &str {
+        GOOGLE_ISSUERS[0]
+    }
+
+    fn identifier(claims: &GoogleClaims) -> Result<String, OidcErr> {
+        Ok(claims.email.clone())
+    }
+
+    fn nonce(claims: &GoogleClaims) -> Result<String, OidcErr> {
+        Ok(claims.nonce.clone())
+    }
+
+    fn validate_claims(claims: &GoogleClaims, policy: &ValidationPolicy) -> Result<(), OidcErr> {
+        validate_google_account(policy, claims.email_verified, claims.hd.as_deref())
+    }
+}
+
+/// Checks that the Google account owns its email address and, if the policy requires it, belongs
+/// to the expected Workspace domain.
+pub(crate) fn validate_google_account(
+    policy: &ValidationPolicy,
+    email_verified: Option<bool>,
+    hd: Option<&str>,
+) -> Result<(), OidcErr> {
+    if email_verified != Some(true) {
+        return Err(OidcErr::EmailNotVerifiedError);
+    }
+    if let Some(expected) = &policy.hosted_domain {
+        if hd != Some(expected.as_str()) {
+            return Err(OidcErr::HostedDomainMismatchError);
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,53 @@
+use serde::{Deserialize, Serialize};
+
+use super::{verified_email, Provider};
+use crate::OidcErr;
+
+/// Microsoft Entra ID (v2.0 endpoint).
+///
+/// Entra does not verify the `email` claim of accounts by itself. The email is only accepted when
+/// the tenant emits the optional `xms_edov` claim, which asserts that the domain owner verified it.
+pub struct Microsoft;
+
+/// Issuer of ID tokens for the Entra tenant `tenant_id`.
+pub fn microsoft_issuer(tenant_id: &str) -> String {
+    format!("https://login.microsoftonline.com/{tenant_id}/v2.0")
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone)]
+pub struct MicrosoftClaims {
+    pub aud: String,
+    pub iss: String,
+    pub sub: String,
+    pub nonce: Option<String>,
+    pub email: Option<String>,
+    pub xms_edov: Option<bool>,
+    pub oid: Option<String>,
+    pub tid: Option<String>,
+    pub preferred_username: Option<String>,
+    pub name: Option<String>,
+}
+
+impl Provider for Microsoft {
+    type Claims = MicrosoftClaims;
+
+    fn issuer(claims: &MicrosoftClaims) -> &str {
+        &claims.iss
+    }
+
+    fn audiences(claims: &MicrosoftClaims) -> Vec<&str> {
+        vec![&claims.aud]
+    }
+
+    fn subject(claims: &MicrosoftClaims) -> &str {
+        &claims.sub
+    }
+
+    fn identifier(claims: &MicrosoftClaims) -> Result<String, OidcErr> {
+        verified_email(claims.email.as_ref(), claims.xms_edov)
+    }
+
+    fn nonce(claims: &MicrosoftClaims) -> Result<String, OidcErr> {
+        claims.nonce.clone().ok_or(OidcErr::NonceMissingError)
+    }
+}
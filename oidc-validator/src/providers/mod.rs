@@ -0,0 +1,145 @@
+//! OpenID Connect providers whose ID tokens can back a claim.
+
+mod apple;
+mod auth0;
+mod generic;
+mod github;
+mod google;
+mod microsoft;
+
+pub use apple::{Apple, AppleClaims, APPLE_ISSUER};
+pub use auth0::{auth0_issuer, Auth0};
+pub use generic::{Generic, StandardClaims};
+pub use github::{GitHubActions, GitHubActionsClaims, GITHUB_ACTIONS_ISSUER};
+pub use google::{Google, GoogleClaims};
+pub use microsoft::{microsoft_issuer, Microsoft, MicrosoftClaims};
+
+pub(crate) use google::validate_google_account;
+
+use common::{JwkKeys, ValidationPolicy};
+use serde::{Deserialize, Serialize};
+
+use crate::{decode_token, OidcErr, TimeOptions, ValidatedToken};
+
+/// An OpenID Connect provider: the shape of its ID tokens and where the claim identifier and
+/// nonce are found in them.
+pub trait Provider {
+    /// Custom claims of the provider's ID tokens.
+    type Claims: for<'de> Deserialize<'de> + Serialize + Clone;
+
+    /// The `iss` claim.
+    fn issuer(claims: &Self::Claims) -> &str;
+
+    /// The values of the `aud` claim.
+    fn audiences(claims: &Self::Claims) -> Vec<&str>;
+
+    /// The `azp` claim, if the provider sets one.
+    fn authorized_party(_claims: &Self::Claims) -> Option<&str> {
+        None
+    }
+
+    /// The `sub` claim, unique and never reassigned within the issuer.
+    fn subject(claims: &Self::Claims) -> &str;
+
+    /// The issuer subjects are scoped to. Providers whose tokens carry several spellings of the
+    /// same issuer return a single one, so subject claim IDs do not depend on it.
+    fn subject_issuer(claims: &Self::Claims) -> &str {
+        Self::issuer(claims)
+    }
+
+    /// The identifier funds are claimed for, e.g. a verified email address.
+    fn identifier(claims: &Self::Claims) -> Result<String, OidcErr>;
+
+    /// The nonce binding the token to a recipient.
+    fn nonce(claims: &Self::Claims) -> Result<String, OidcErr>;
+
+    /// Provider specific checks, run after the policy has been enforced.
+    fn validate_claims(_claims: &Self::Claims, _policy: &ValidationPolicy) -> Result<(), OidcErr> {
+        Ok(())
+    }
+}
+
+/// Validates `token` as an ID token of `P`.
+pub fn validate<P: Provider>(
+    token: &str,
+    keys: &JwkKeys,
+    policy: &ValidationPolicy,
+    time: &TimeOptions,
+) -> Result<ValidatedToken, OidcErr> {
+    let decoded = decode_token::<P::Claims>(token, keys)?;
+    let key_digest = decoded.key.digest().ok_or(OidcErr::CertificateParseError)?;
+    let claims = decoded.claims;
+    // ID tokens always carry `exp` and `iat`; a token without them cannot be bounded in time.
+    if claims.expiration.is_none() || claims.issued_at.is_none() {
+        return Err(OidcErr::TimeClaimMissingError);
+    }
+    time.validate_claims(&claims)?;
+
+    let claims = claims.custom;
+    validate_policy(
+        policy,
+        P::issuer(&claims),
+        &P::audiences(&claims),
+        P::authorized_party(&claims),
+    )?;
+    P::validate_claims(&claims, policy)?;
+
+    Ok(ValidatedToken {
+        issuer: P::subject_issuer(&claims).to_string(),
+        subject: P::subject(&claims).to_string(),
+        identifier: P::identifier(&claims)?,
+        nonce: P::nonce(&claims)?,
+        key_digest,
+    })
+}
+
+/// Checks the issuer, audiences and authorized party of a token against `policy`.
+pub(crate) fn validate_policy(
+    policy: &ValidationPolicy,
+    iss: &str,
+    aud: &[&str],
+    azp: Option<&str>,
+) -> Result<(), OidcErr> {
+    if !policy.issuers.iter().any(|allowed| allowed == iss) {
+        return Err(OidcErr::IssuerNotAllowedError);
+    }
+    if !policy
+        .audiences
+        .iter()
+        .any(|allowed| aud.contains(&allowed.as_str()))
+    {
+        return Err(OidcErr::AudienceNotAllowedError);
+    }
+    if let Some(expected) = &policy.authorized_party {
+        if azp != Some(expected.as_str()) {
+            return Err(OidcErr::AuthorizedPartyMismatchError);
+        }
+    }
+    Ok(())
+}
+
+/// The `aud` claim, which providers may encode as a single string or an array.
+#[derive(Deserialize, Serialize, Debug, Clone)]
+#[serde(untagged)]
+pub enum Audience {
+    Single(String),
+    Multiple(Vec<String>),
+}
+
+impl Audience {
+    pub fn to_vec(&self) -> Vec<&str> {
+        match self {
+            Self::Single(aud) => vec![aud.as_str()],
+            Self::Multiple(aud) => aud.iter().map(String::as_str).collect(),
+        }
+    }
+}
+
+/// Returns `email` if the provider asserts that the account owns it.
+fn verified_email(email: Option<&String>, email_verified: Option<bool>) -> Result<String, OidcErr> {
+    let email = email.ok_or(OidcErr::IdentifierMissingError)?;
+    if email_verified != Some(true) {
+        return Err(OidcErr::EmailNotVerifiedError);
+    }
+    Ok(email.clone())
+}
@@ -1,5 +1,29 @@
+use std::path::Path;
+
 use sp1_helper::build_program;
 
 fn main() {
-    build_program("../program")
+    build_program("../program");
+    emit_sp1_sdk_version();
+}
+
+/// Passes the locked version of `sp1-sdk` to the crate as `SP1_SDK_VERSION`, which names the
+/// cached keys, see `ProverContext`.
+fn emit_sp1_sdk_version() {
+    let lock = Path::new(env!("CARGO_MANIFEST_DIR")).join("../Cargo.lock");
+    println!("cargo:rerun-if-changed={}", lock.display());
+
+    let version = std::fs::read_to_string(&lock)
+        .ok()
+        .and_then(|lock| {
+            let mut lines = lock.lines();
+            lines.find(|line| *line == r#"name = "sp1-sdk""#)?;
+            let version = lines
+                .next()?
+                .strip_prefix("version = \"")?
+                .strip_suffix('"')?;
+            Some(version.to_string())
+        })
+        .unwrap_or_else(|| "unknown".to_string());
+    println!("cargo:rustc-env=SP1_SDK_VERSION={version}");
 }
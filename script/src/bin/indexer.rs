@@ -0,0 +1,58 @@
+//! Indexes the deposits and claims of SP1 Pay's contract into a SQLite database, and serves
+//! `GET /claims/{claimId}` and `GET /totals` from it.
+//!
+//! The node and the contract are read from `RPC_URL` and `BONSAI_PAY_ADDRESS`, see `Config`:
+//! ```shell
+//! RUST_LOG=info cargo run --release --bin sp1-pay-indexer -- --from-block <DEPLOYMENT_BLOCK>
+//! ```
+
+use std::{net::SocketAddr, path::PathBuf, sync::Arc, time::Duration};
+
+use anyhow::Result;
+use clap::Parser;
+use sp1_pay_script::{
+    config::Config,
+    events::EventStore,
+    indexer::{self, Indexer, DEFAULT_POLL_INTERVAL},
+};
+
+#[derive(Parser, Debug)]
+#[clap(author, version, about, long_about = None)]
+struct Cli {
+    #[clap(flatten)]
+    config: Config,
+
+    #[clap(long, default_value = "127.0.0.1:8081")]
+    addr: SocketAddr,
+
+    /// SQLite database the events are stored in.
+    #[clap(long, default_value = "events.sqlite")]
+    db: PathBuf,
+
+    /// Block to start indexing at when the database is empty, such as the contract's deployment.
+    #[clap(long, default_value_t = 0)]
+    from_block: u64,
+
+    /// Blocks on top of a block before it is indexed.
+    #[clap(long, default_value_t = 0)]
+    confirmations: u64,
+
+    /// Seconds between polls of the node.
+    #[clap(long, default_value_t = DEFAULT_POLL_INTERVAL.as_secs())]
+    poll_interval: u64,
+}
+
+#[tokio::main]
+async fn main() -> Result<()> {
+    dotenv::dotenv().ok();
+    let cli = Cli::parse();
+
+    // Setup the logger.
+    sp1_sdk::utils::setup_logger();
+
+    let store = Arc::new(EventStore::open(&cli.db)?);
+    let indexer = Indexer::new(cli.config.rpc_url()?, cli.config.contract()?, store)?
+        .with_start_block(cli.from_block)
+        .with_confirmations(cli.confirmations);
+    indexer::serve(indexer, cli.addr, Duration::from_secs(cli.poll_interval)).await
+}
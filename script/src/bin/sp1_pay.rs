@@ -0,0 +1,628 @@
+//! Command line interface of SP1 Pay: proves claims, prepares deployments and serves the prover.
+//!
+//! Settings shared by the commands are read from the arguments, the environment and `.env`, see
+//! `Config`. For example, to prove a claim and test its verification in Solidity:
+//! ```shell
+//! RUST_LOG=info cargo run --release --bin sp1-pay -- execute --jwt <ID_TOKEN> --nonce <PREIMAGE>
+//! RUST_LOG=info cargo run --release --bin sp1-pay -- prove --mode plonk --jwt <ID_TOKEN> --nonce <PREIMAGE>
+//! cargo run --release --bin sp1-pay -- fixture --proof proof.bin
+//! ```
+
+use std::{
+    net::SocketAddr,
+    path::{Path, PathBuf},
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use alloy_primitives::{Address, U256};
+use alloy_sol_types::SolType;
+use anyhow::{anyhow, Context, Result};
+use clap::{Args, Parser, Subcommand, ValueEnum};
+use common::{
+    contract::{ContractError, IBonsaiPay, IBonsaiPayEvents},
+    jwks::Jwks,
+    ClaimKey, ClaimNonce, ProofInputs, ProofOutputs, GOOGLE_ISSUERS,
+};
+use ethers::providers::{Http, Provider};
+use jwt_compact::UntrustedToken;
+use serde::Deserialize;
+use sp1_pay_script::{
+    claim::{claim_calldata, contract_events},
+    config::Config,
+    context::ProverContext,
+    deposit::{deposit, read_deposits, DepositRecipient},
+    execute,
+    fixture::ProofFixture,
+    keys::{KeyRegistry, DEFAULT_GRACE_PERIOD},
+    nonce::{new_claim_nonce, DEFAULT_NONCE_TTL},
+    preflight::{preflight, view},
+    relayer::RelayerArgs,
+    salt::{generate_salt, parse_salt},
+    server,
+    tx::{TxError, TxSender},
+};
+use sp1_sdk::{HashableKey, SP1ProofWithPublicValues, SP1Stdin};
+
+#[derive(Parser, Debug)]
+#[clap(author, version, about, long_about = None)]
+struct Cli {
+    #[clap(flatten)]
+    config: Config,
+
+    #[clap(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand, Debug)]
+enum Command {
+    /// Print the program verification key.
+    Vkey,
+    /// Print the signing keys of the issuer.
+    FetchJwks {
+        /// Write the keys to a file instead, e.g. to pass later as `--jwks-file`.
+        #[clap(long)]
+        output: Option<PathBuf>,
+    },
+    /// Print the settings to deploy `BonsaiPay` with.
+    Genesis,
+    /// Execute the program on a claim without proving it, and report its cycles.
+    Execute(ClaimArgs),
+    /// Prove a claim.
+    Prove {
+        #[clap(flatten)]
+        claim: ClaimArgs,
+
+        #[clap(long, value_enum, default_value_t = ProofMode::Plonk)]
+        mode: ProofMode,
+
+        /// File the proof is saved to.
+        #[clap(long, default_value = "proof.bin")]
+        output: PathBuf,
+
+        /// Prove even if the contract at `--contract` would not accept the proof.
+        #[clap(long)]
+        skip_preflight: bool,
+    },
+    /// Verify a saved proof against the program verification key.
+    Verify {
+        #[clap(long, default_value = "proof.bin")]
+        proof: PathBuf,
+    },
+    /// Write a saved PLONK proof as a fixture for the Solidity tests.
+    Fixture {
+        #[clap(long, default_value = "proof.bin")]
+        proof: PathBuf,
+
+        #[clap(long, default_value = "../contracts/src/fixtures/fixture.json")]
+        output: PathBuf,
+    },
+    /// Print the calldata of `BonsaiPay.claim` for a saved PLONK proof.
+    ClaimCalldata {
+        #[clap(long, default_value = "proof.bin")]
+        proof: PathBuf,
+    },
+    /// Send a saved PLONK proof to `BonsaiPay.claim`, and wait for the transaction to be mined.
+    /// The claim is simulated first, and not sent if it would revert.
+    Claim {
+        #[clap(long, default_value = "proof.bin")]
+        proof: PathBuf,
+    },
+    /// Serve `GET /auth`, proving and claiming for the ID token in `X-Auth-Token`.
+    Serve {
+        #[clap(long, default_value = "127.0.0.1:8080")]
+        addr: SocketAddr,
+
+        /// SQLite database the claims are queued in.
+        #[clap(long, default_value = "jobs.sqlite")]
+        jobs: PathBuf,
+
+        #[clap(flatten)]
+        relayer: RelayerArgs,
+    },
+    /// Print a nonce to request an ID token with, and the preimage to prove the claim with.
+    Nonce {
+        /// Address the claimed funds are sent to.
+        #[clap(long)]
+        recipient: Address,
+
+        /// Seconds the nonce stays valid.
+        #[clap(long, default_value_t = DEFAULT_NONCE_TTL)]
+        ttl: u64,
+
+        /// Address of the prover server relaying the claim, paid `--fee`. See its `GET /relayer`.
+        #[clap(long, default_value_t = Address::ZERO)]
+        relayer: Address,
+
+        /// Fee in wei paid to `--relayer` out of the claimed balance.
+        #[clap(long, default_value_t = U256::ZERO)]
+        fee: U256,
+    },
+    /// Print the claim IDs to deposit to for an account, keyed on its email address and on its
+    /// subject. With `--salted` a new salt is generated; share it with the claimant.
+    ClaimId(ClaimIdArgs),
+    /// Deposit to the claim ID of an account and wait for the deposit to be mined, or to each
+    /// recipient of a CSV file with `--csv`.
+    Deposit(DepositArgs),
+    /// Compare the signing keys registered on the contract with those the issuer publishes.
+    ///
+    /// Run it periodically, e.g. from cron, and apply the printed changes with `registerKey` and
+    /// `revokeKey`.
+    Keys {
+        /// File the current and previous keys are tracked in between runs.
+        #[clap(long, default_value = "keys.json")]
+        state: PathBuf,
+
+        /// Seconds a key stays registered after the issuer stopped publishing it.
+        #[clap(long, default_value_t = DEFAULT_GRACE_PERIOD)]
+        grace_period: u64,
+    },
+}
+
+#[derive(ValueEnum, Clone, Copy, Debug)]
+enum ProofMode {
+    /// Uncompressed STARK proof, the fastest to generate.
+    Core,
+    /// Constant-size STARK proof.
+    Compressed,
+    /// PLONK proof, verifiable on-chain.
+    Plonk,
+}
+
+#[derive(ValueEnum, Clone, Copy, Debug)]
+enum ClaimKeyArg {
+    Identifier,
+    Subject,
+    NormalizedIdentifier,
+    GmailFoldedIdentifier,
+}
+
+impl From<ClaimKeyArg> for ClaimKey {
+    fn from(key: ClaimKeyArg) -> Self {
+        match key {
+            ClaimKeyArg::Identifier => ClaimKey::Identifier,
+            ClaimKeyArg::Subject => ClaimKey::Subject,
+            ClaimKeyArg::NormalizedIdentifier => ClaimKey::NormalizedIdentifier,
+            ClaimKeyArg::GmailFoldedIdentifier => ClaimKey::GmailFoldedIdentifier,
+        }
+    }
+}
+
+/// The claim to execute or prove.
+#[derive(Args, Debug)]
+struct ClaimArgs {
+    /// ID token of the claimant.
+    #[clap(long, env = "JWT", hide_env_values = true)]
+    jwt: String,
+
+    /// JSON preimage of the token's nonce, as printed by `nonce`.
+    #[clap(long)]
+    nonce: String,
+
+    /// What the deposit's claim ID is derived from.
+    #[clap(long, value_enum, default_value_t = ClaimKeyArg::Identifier)]
+    claim_key: ClaimKeyArg,
+
+    /// Salt of the deposit's claim ID, if it is salted.
+    #[clap(long)]
+    salt: Option<String>,
+
+    /// Time the token is validated at, in seconds since the UNIX epoch. Defaults to now.
+    #[clap(long)]
+    timestamp: Option<u64>,
+}
+
+impl ClaimArgs {
+    async fn inputs(&self, config: &Config) -> Result<ProofInputs> {
+        let nonce: ClaimNonce =
+            serde_json::from_str(&self.nonce).context("parsing the nonce preimage")?;
+        let timestamp = match self.timestamp {
+            Some(timestamp) => timestamp,
+            None => now()?,
+        };
+
+        Ok(ProofInputs {
+            identity_provider: config.identity_provider(),
+            jwt: self.jwt.clone(),
+            cert: config.fetch_jwks().await?.raw.clone(),
+            timestamp,
+            policy: config.policy()?,
+            claim_key: self.claim_key.into(),
+            salt: self.salt.as_deref().map(parse_salt).transpose()?,
+            nonce,
+        })
+    }
+}
+
+#[derive(Args, Debug)]
+struct ClaimIdArgs {
+    /// Email address the deposit is claimable by.
+    #[clap(long)]
+    email: Option<String>,
+
+    /// Subject (`sub`) of the account the deposit is claimable by.
+    #[clap(long)]
+    subject: Option<String>,
+
+    /// ID token of the account to read the email address and subject from. Its signature is
+    /// not checked.
+    #[clap(long)]
+    token: Option<String>,
+
+    /// Salt the claim IDs with a newly generated salt.
+    #[clap(long, conflicts_with = "salt")]
+    salted: bool,
+
+    /// Salt the claim IDs with an existing salt.
+    #[clap(long)]
+    salt: Option<String>,
+
+    /// Issuer of the account's tokens, for the claim ID keyed on the subject.
+    #[clap(long, default_value = GOOGLE_ISSUERS[0])]
+    issuer: String,
+}
+
+#[derive(Args, Debug)]
+struct DepositArgs {
+    /// Email address the deposit is claimable by.
+    #[clap(long, conflicts_with_all = ["subject", "csv"])]
+    email: Option<String>,
+
+    /// Subject (`sub`) of the account the deposit is claimable by.
+    #[clap(long, conflicts_with = "csv")]
+    subject: Option<String>,
+
+    /// Key the deposit on the email address with ASCII letters lowercased.
+    #[clap(long, requires = "email", conflicts_with = "gmail_folded")]
+    normalized: bool,
+
+    /// Key the deposit on the Gmail address with dots and `+` suffixes removed.
+    #[clap(long, requires = "email")]
+    gmail_folded: bool,
+
+    /// Amount in wei.
+    #[clap(long, conflicts_with = "csv", required_unless_present = "csv")]
+    amount: Option<U256>,
+
+    /// CSV file of deposits, with `recipient` and `amount` columns and optional `key` and `salt`
+    /// columns, see `parse_deposits`.
+    #[clap(long)]
+    csv: Option<PathBuf>,
+
+    /// Salt the claim IDs without a salt with a newly generated salt each.
+    #[clap(long, conflicts_with = "salt")]
+    salted: bool,
+
+    /// Salt the claim ID with an existing salt.
+    #[clap(long, conflicts_with = "csv")]
+    salt: Option<String>,
+
+    /// Issuer of the recipients' tokens, for deposits keyed on the subject.
+    #[clap(long, default_value = GOOGLE_ISSUERS[0])]
+    issuer: String,
+}
+
+#[derive(Deserialize)]
+struct TokenIdentity {
+    sub: String,
+    email: Option<String>,
+}
+
+#[tokio::main]
+async fn main() -> Result<()> {
+    dotenv::dotenv().ok();
+    let cli = Cli::parse();
+    let config = cli.config;
+    // Only the commands that run the program set a prover up; `serve` sets up its own.
+    let context = || ProverContext::new(config.key_cache.clone());
+
+    // Setup the logger.
+    sp1_sdk::utils::setup_logger();
+
+    match cli.command {
+        Command::Vkey => {
+            println!("Program Verification Key: {}", context().vk().bytes32());
+        }
+        Command::FetchJwks { output } => {
+            let jwks = config.fetch_jwks().await?;
+            match output {
+                Some(path) => std::fs::write(&path, &jwks.raw)
+                    .with_context(|| format!("writing {}", path.display()))?,
+                None => println!("{}", String::from_utf8_lossy(&jwks.raw)),
+            }
+        }
+        Command::Genesis => genesis(&config, &context()).await?,
+        Command::Execute(claim) => {
+            let inputs = claim.inputs(&config).await?;
+            let (public_values, report) = execute(context().client(), &inputs)?;
+            let outputs = ProofOutputs::abi_decode(public_values.as_slice(), true)
+                .context("decoding the public values")?;
+
+            println!("Cycles: {}", report.total_instruction_count());
+            println!("Claim ID: {}", outputs.claimId);
+            println!("Recipient: {}", outputs.msgSender);
+        }
+        Command::Prove {
+            claim,
+            mode,
+            output,
+            skip_preflight,
+        } => {
+            let inputs = claim.inputs(&config).await?;
+            let context = context();
+
+            // Without a deployment to compare with, the proof is only checked by `verify`.
+            if let (Some(rpc_url), Some(contract), false) =
+                (&config.rpc_url, config.contract, skip_preflight)
+            {
+                let jwks = Jwks::parse(inputs.cert.clone())?;
+                preflight(
+                    rpc_url,
+                    contract,
+                    &inputs,
+                    &jwks.keys,
+                    &context.vk().bytes32(),
+                )
+                .await
+                .context("the contract would not accept the proof")?;
+            }
+
+            // Execute first so a rejected token is reported before spending time on proving.
+            execute(context.client(), &inputs)?;
+
+            let mut stdin = SP1Stdin::new();
+            stdin.write(&inputs);
+            let prover = context.client().prove(context.pk(), stdin);
+            let proof = match mode {
+                ProofMode::Core => prover.run(),
+                ProofMode::Compressed => prover.compressed().run(),
+                ProofMode::Plonk => prover.plonk().run(),
+            }?;
+
+            proof.save(&output)?;
+            println!("Proof saved to {}", output.display());
+        }
+        Command::Verify { proof } => {
+            let context = context();
+            context
+                .client()
+                .verify(&load_proof(&proof)?, context.vk())
+                .map_err(|err| anyhow!("invalid proof: {err}"))?;
+            println!("Proof verified");
+        }
+        Command::Fixture { proof, output } => {
+            let fixture = ProofFixture::new(&load_proof(&proof)?, context().vk())?;
+            fixture.save(&output)?;
+
+            println!("Verification Key: {}", fixture.vkey);
+            println!("Public Values: {}", fixture.public_values);
+            println!("Fixture saved to {}", output.display());
+        }
+        Command::ClaimCalldata { proof } => {
+            println!("{}", claim_calldata(&load_proof(&proof)?));
+        }
+        Command::Claim { proof } => {
+            let calldata = claim_calldata(&load_proof(&proof)?);
+            let sender = TxSender::from_config(&config)?;
+            let contract = ethers::types::Address::from(config.contract()?.into_array());
+            match sender.simulate(contract, calldata.to_vec().into()).await {
+                Err(TxError::CallReverted(data)) => {
+                    return Err(anyhow!(
+                        "claim would revert: {}",
+                        ContractError::decode(&data)
+                    ))
+                }
+                result => result?,
+            }
+            let hash = sender.send(contract, calldata.to_vec().into()).await?;
+            println!("Claim transaction: {hash:?}");
+            let receipt = sender.wait(hash).await?;
+            for event in contract_events(&receipt, contract) {
+                match event {
+                    IBonsaiPayEvents::Claimed(claimed) => println!(
+                        "Claimed {} wei for {} (claim ID {})",
+                        claimed.amount, claimed.recipient, claimed.claimId
+                    ),
+                    IBonsaiPayEvents::RelayerPaid(paid) => {
+                        println!("Paid {} wei to relayer {}", paid.fee, paid.relayer)
+                    }
+                    _ => {}
+                }
+            }
+        }
+        Command::Serve {
+            addr,
+            jobs,
+            relayer,
+        } => server::serve(config, &relayer, addr, &jobs).await?,
+        Command::Nonce {
+            recipient,
+            ttl,
+            relayer,
+            fee,
+        } => {
+            let nonce = new_claim_nonce(
+                recipient,
+                config.chain_id()?,
+                config.contract()?,
+                ttl,
+                relayer,
+                fee,
+            );
+            println!("NONCE={}", nonce.nonce());
+            println!("PREIMAGE={}", serde_json::to_string(&nonce)?);
+        }
+        Command::ClaimId(args) => claim_id(args)?,
+        Command::Deposit(args) => deposits(&config, args).await?,
+        Command::Keys {
+            state,
+            grace_period,
+        } => keys(&config, &state, grace_period).await?,
+    }
+
+    Ok(())
+}
+
+fn now() -> Result<u64> {
+    Ok(SystemTime::now().duration_since(UNIX_EPOCH)?.as_secs())
+}
+
+fn load_proof(path: &Path) -> Result<SP1ProofWithPublicValues> {
+    SP1ProofWithPublicValues::load(path).with_context(|| format!("loading {}", path.display()))
+}
+
+async fn genesis(config: &Config, context: &ProverContext) -> Result<()> {
+    let jwks = config.fetch_jwks().await?;
+    let key_digests: Vec<String> = jwks
+        .keys
+        .keys
+        .iter()
+        .filter_map(|key| key.digest())
+        .map(|digest| digest.to_string())
+        .collect();
+
+    println!(
+        "KEY_DIGESTS={}\nSP1_PAY_PROGRAM_VKEY={}\nPOLICY_HASH={}",
+        key_digests.join(","),
+        context.vk().bytes32(),
+        config.policy()?.hash()
+    );
+    Ok(())
+}
+
+fn claim_id(args: ClaimIdArgs) -> Result<()> {
+    let (mut email, mut subject) = (args.email, args.subject);
+    if let Some(token) = &args.token {
+        let claims = UntrustedToken::new(token)
+            .map_err(|err| anyhow!("malformed ID token: {err}"))?
+            .deserialize_claims_unchecked::<TokenIdentity>()
+            .context("ID token has no subject")?
+            .custom;
+        email = email.or(claims.email);
+        subject = subject.or(Some(claims.sub));
+    }
+    if email.is_none() && subject.is_none() {
+        return Err(anyhow!("pass --email, --subject or --token"));
+    }
+
+    let salt = match (args.salted, args.salt) {
+        (true, _) => Some(generate_salt()),
+        (false, Some(salt)) => Some(parse_salt(&salt)?),
+        (false, None) => None,
+    };
+
+    let keys = [
+        (ClaimKey::Identifier, &email),
+        (ClaimKey::NormalizedIdentifier, &email),
+        (ClaimKey::GmailFoldedIdentifier, &email),
+        (ClaimKey::Subject, &subject),
+    ];
+    for (claim_key, identity) in keys {
+        let Some(identity) = identity else {
+            continue;
+        };
+        let recipient = DepositRecipient {
+            claim_key,
+            identity: identity.clone(),
+            salt,
+        };
+        let (scheme, claim_id) = recipient.claim_id(&args.issuer);
+        println!(
+            "{claim_key:?}: CLAIM_ID={claim_id} CLAIM_SCHEME={}",
+            scheme.id()
+        );
+    }
+    if let Some(salt) = salt {
+        println!("SALT={salt}");
+    }
+
+    Ok(())
+}
+
+async fn deposits(config: &Config, args: DepositArgs) -> Result<()> {
+    let deposits: Vec<(DepositRecipient, U256)> = match &args.csv {
+        Some(csv) => read_deposits(csv)?
+            .into_iter()
+            .map(|row| (row.recipient, row.amount))
+            .collect(),
+        None => {
+            let (claim_key, identity) = match (args.email, args.subject) {
+                (Some(email), _) if args.gmail_folded => (ClaimKey::GmailFoldedIdentifier, email),
+                (Some(email), _) if args.normalized => (ClaimKey::NormalizedIdentifier, email),
+                (Some(email), _) => (ClaimKey::Identifier, email),
+                (None, Some(subject)) => (ClaimKey::Subject, subject),
+                (None, None) => return Err(anyhow!("pass --email, --subject or --csv")),
+            };
+            let salt = args.salt.as_deref().map(parse_salt).transpose()?;
+            let recipient = DepositRecipient {
+                claim_key,
+                identity,
+                salt,
+            };
+            vec![(recipient, args.amount.context("pass --amount")?)]
+        }
+    };
+
+    let sender = TxSender::from_config(config)?;
+    let contract = config.contract()?;
+    for (mut recipient, amount) in deposits {
+        if args.salted && recipient.salt.is_none() {
+            recipient.salt = Some(generate_salt());
+        }
+        let deposit = deposit(&sender, contract, &args.issuer, &recipient, amount)
+            .await
+            .with_context(|| format!("depositing to {}", recipient.identity))?;
+
+        print!(
+            "{}: CLAIM_ID={} CLAIM_SCHEME={} AMOUNT={} BALANCE={}",
+            recipient.identity,
+            deposit.claim_id,
+            deposit.scheme.id(),
+            amount,
+            deposit.previous_balance + amount,
+        );
+        if let Some(salt) = recipient.salt {
+            print!(" SALT={salt}");
+        }
+        println!(" TX={:?}", deposit.transaction_hash);
+    }
+
+    Ok(())
+}
+
+async fn keys(config: &Config, state: &Path, grace_period: u64) -> Result<()> {
+    let now = now()?;
+    let jwks = config.fetch_jwks().await?;
+
+    let mut registry = KeyRegistry::load(state, grace_period)?;
+    registry.observe(&jwks.keys, now);
+    registry.save(state)?;
+
+    let provider = Provider::<Http>::try_from(config.rpc_url()?)?;
+    let registered = view(
+        &provider,
+        config.contract()?,
+        IBonsaiPay::registeredKeyDigestsCall {},
+    )
+    .await?
+    ._0;
+
+    for key in registry.current() {
+        println!("current  {} (kid {})", key.digest, key.key_id);
+    }
+    for key in registry.previous(now) {
+        println!("previous {} (kid {})", key.digest, key.key_id);
+    }
+
+    let plan = registry.plan(&registered, now);
+    for digest in &plan.register {
+        println!("register {digest}");
+    }
+    for digest in &plan.stale {
+        println!("stale    {digest}");
+    }
+    if plan.register.is_empty() && plan.stale.is_empty() {
+        println!("registered keys are up to date");
+    }
+
+    Ok(())
+}
@@ -0,0 +1,86 @@
+//! Transactions claiming deposits with a proof.
+
+use alloy_primitives::{Bytes, B256};
+use alloy_sol_types::SolCall;
+use common::contract::{decode_event, IBonsaiPay, IBonsaiPayEvents};
+use ethers::types::{Address, Log, TransactionReceipt};
+use sp1_sdk::SP1ProofWithPublicValues;
+
+/// Calldata of `BonsaiPay.claim` for `proof`.
+///
+/// Proofs of the mock prover are sent without proof bytes, as `SP1MockVerifier` expects.
+pub fn claim_calldata(proof: &SP1ProofWithPublicValues) -> Bytes {
+    let is_mock = std::env::var("SP1_PROVER").is_ok_and(|prover| prover.to_lowercase() == "mock");
+    let proof_bytes = if is_mock { Vec::new() } else { proof.bytes() };
+
+    IBonsaiPay::claimCall {
+        proof: Bytes::from(proof_bytes),
+        publicValues: Bytes::from(proof.public_values.to_vec()),
+    }
+    .abi_encode()
+    .into()
+}
+
+/// The events `contract` emitted in the transaction of `receipt`, such as `Claimed`.
+pub fn contract_events(receipt: &TransactionReceipt, contract: Address) -> Vec<IBonsaiPayEvents> {
+    receipt
+        .logs
+        .iter()
+        .filter(|log| log.address == contract)
+        .filter_map(contract_event)
+        .collect()
+}
+
+/// Decodes `log` as an event of `BonsaiPay`, whatever contract emitted it.
+pub fn contract_event(log: &Log) -> Option<IBonsaiPayEvents> {
+    let topics: Vec<B256> = log.topics.iter().map(|topic| B256::from(topic.0)).collect();
+    decode_event(&topics, &log.data)
+}
+
+#[cfg(test)]
+mod test_claim {
+    use alloy_primitives::U256;
+    use alloy_sol_types::SolEvent;
+    use common::contract::{IBonsaiPay, IBonsaiPayEvents};
+    use ethers::types::{Address, Log, TransactionReceipt, H256};
+
+    use super::contract_events;
+
+    fn log(address: Address, event: &impl SolEvent) -> Log {
+        let data = event.encode_log_data();
+        Log {
+            address,
+            topics: data.topics().iter().map(|topic| H256(topic.0)).collect(),
+            data: data.data.to_vec().into(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_contract_events() {
+        let contract = Address::repeat_byte(0xbb);
+        let claimed = IBonsaiPay::Claimed {
+            recipient: [0x11; 20].into(),
+            claimId: [0xc1; 32].into(),
+            amount: U256::from(1_000),
+        };
+        let receipt = TransactionReceipt {
+            logs: vec![
+                log(contract, &claimed),
+                // The recipient's contract may emit events with the same signature.
+                log(Address::repeat_byte(0x11), &claimed),
+                Log {
+                    address: contract,
+                    topics: vec![H256::repeat_byte(0xff)],
+                    ..Default::default()
+                },
+            ],
+            ..Default::default()
+        };
+
+        assert_eq!(
+            contract_events(&receipt, contract),
+            vec![IBonsaiPayEvents::Claimed(claimed)]
+        );
+    }
+}
@@ -0,0 +1,185 @@
+//! Settings shared by the `sp1-pay` commands.
+//!
+//! `.env` is loaded before the arguments are parsed, so every setting can come from the command
+//! line, the environment or `.env`, in that order of precedence.
+
+use std::{path::PathBuf, sync::Arc};
+
+use alloy_primitives::{Address, U256};
+use anyhow::{anyhow, Result};
+use clap::Args;
+use common::{
+    jwks::{Jwks, JwksClient, GOOGLE_JWKS_URL},
+    ValidationPolicy, DEFAULT_LEEWAY,
+};
+
+#[derive(Args, Debug, Clone)]
+pub struct Config {
+    /// Identity provider tokens are validated with, see `oidc_validator::IdentityProvider`.
+    #[clap(long, env, default_value_t = 0, global = true)]
+    pub identity_provider: u32,
+
+    /// URL of the issuer's signing keys.
+    #[clap(long, env, default_value = GOOGLE_JWKS_URL, global = true)]
+    pub jwks_url: String,
+
+    /// Read the signing keys from a file instead of `jwks_url`.
+    #[clap(long, env, global = true)]
+    pub jwks_file: Option<PathBuf>,
+
+    /// OAuth client ID that Google ID tokens must be issued for.
+    #[clap(long, env, global = true)]
+    pub google_client_id: Option<String>,
+
+    /// Google Workspace domain that claimants must belong to, if any.
+    #[clap(long, env, global = true)]
+    pub google_hosted_domain: Option<String>,
+
+    /// Issuer accepted instead of Google's, for other identity providers.
+    #[clap(long, env, global = true, requires = "policy_audience")]
+    pub policy_issuer: Option<String>,
+
+    /// Audience accepted together with `policy_issuer`.
+    #[clap(long, env, global = true, requires = "policy_issuer")]
+    pub policy_audience: Option<String>,
+
+    /// Seconds of clock skew tolerated when checking the time claims of tokens.
+    #[clap(long, env, default_value_t = DEFAULT_LEEWAY, global = true)]
+    pub policy_leeway: u64,
+
+    /// Directory the program's proving and verifying keys are cached in, see `ProverContext`.
+    #[clap(long, env, global = true)]
+    pub key_cache: Option<PathBuf>,
+
+    /// Ethereum Node endpoint.
+    #[clap(long, env, global = true)]
+    pub rpc_url: Option<String>,
+
+    /// Ethereum chain ID
+    #[clap(long, env, global = true)]
+    pub chain_id: Option<u64>,
+
+    /// Application's contract address on Ethereum
+    #[clap(long, env = "BONSAI_PAY_ADDRESS", global = true)]
+    pub contract: Option<Address>,
+
+    /// Private key of the wallet claims are sent from.
+    #[clap(long, env, global = true, hide_env_values = true)]
+    pub eth_wallet_private_key: Option<String>,
+}
+
+impl Config {
+    pub fn identity_provider(&self) -> U256 {
+        U256::from(self.identity_provider)
+    }
+
+    /// The policy tokens are validated against: `policy_issuer` and `policy_audience` if set,
+    /// Google's issuers and `google_client_id` otherwise, with `policy_leeway`.
+    pub fn policy(&self) -> Result<ValidationPolicy> {
+        match (&self.policy_issuer, &self.policy_audience) {
+            (Some(issuer), Some(audience)) => {
+                return Ok(ValidationPolicy::new(issuer, audience).with_leeway(self.policy_leeway))
+            }
+            (Some(_), None) => return Err(anyhow!("POLICY_ISSUER set without POLICY_AUDIENCE")),
+            (None, Some(_)) => return Err(anyhow!("POLICY_AUDIENCE set without POLICY_ISSUER")),
+            (None, None) => {}
+        }
+
+        let client_id = self
+            .google_client_id
+            .as_deref()
+            .ok_or_else(|| anyhow!("GOOGLE_CLIENT_ID not set"))?;
+        let policy = ValidationPolicy::google(client_id).with_leeway(self.policy_leeway);
+        Ok(match self.google_hosted_domain.as_deref() {
+            Some(domain) if !domain.is_empty() => policy.with_hosted_domain(domain),
+            _ => policy,
+        })
+    }
+
+    /// A client for the issuer's signing keys. Keep it to reuse the keys while they are fresh.
+    pub fn jwks_client(&self) -> JwksClient {
+        match &self.jwks_file {
+            Some(path) => JwksClient::from_file(path),
+            None => JwksClient::new(&self.jwks_url),
+        }
+    }
+
+    /// Fetches the issuer's signing keys once.
+    pub async fn fetch_jwks(&self) -> Result<Arc<Jwks>> {
+        self.jwks_client().fetch().await
+    }
+
+    pub fn rpc_url(&self) -> Result<&str> {
+        self.rpc_url
+            .as_deref()
+            .ok_or_else(|| anyhow!("RPC_URL not set"))
+    }
+
+    pub fn chain_id(&self) -> Result<u64> {
+        self.chain_id.ok_or_else(|| anyhow!("CHAIN_ID not set"))
+    }
+
+    pub fn contract(&self) -> Result<Address> {
+        self.contract
+            .ok_or_else(|| anyhow!("BONSAI_PAY_ADDRESS not set"))
+    }
+
+    pub fn eth_wallet_private_key(&self) -> Result<&str> {
+        self.eth_wallet_private_key
+            .as_deref()
+            .ok_or_else(|| anyhow!("ETH_WALLET_PRIVATE_KEY not set"))
+    }
+}
+
+#[cfg(test)]
+mod test_config {
+    use clap::Parser;
+
+    use super::Config;
+
+    #[derive(Parser)]
+    struct Cli {
+        #[clap(flatten)]
+        config: Config,
+    }
+
+    #[test]
+    fn test_policy_audience_requires_issuer() {
+        assert!(Cli::try_parse_from(["sp1-pay", "--policy-audience", "sp1-pay"]).is_err());
+
+        let cli = Cli::try_parse_from([
+            "sp1-pay",
+            "--policy-issuer",
+            "https://issuer.example.com",
+            "--policy-audience",
+            "sp1-pay",
+        ])
+        .unwrap();
+        let policy = cli.config.policy().unwrap();
+        assert_eq!(policy.issuers, vec!["https://issuer.example.com"]);
+    }
+
+    #[test]
+    fn test_policy_leeway() {
+        let cli = Cli::try_parse_from(["sp1-pay", "--google-client-id", "client-id"]).unwrap();
+        assert_eq!(cli.config.policy().unwrap().leeway, common::DEFAULT_LEEWAY);
+
+        let cli = Cli::try_parse_from([
+            "sp1-pay",
+            "--google-client-id",
+            "client-id",
+            "--policy-leeway",
+            "30",
+        ])
+        .unwrap();
+        assert_eq!(cli.config.policy().unwrap().leeway, 30);
+    }
+
+    #[test]
+    fn test_policy_without_audience_fails() {
+        let mut config = Cli::try_parse_from(["sp1-pay"]).unwrap().config;
+        config.policy_issuer = Some("https://issuer.example.com".to_string());
+        config.google_client_id = Some("client-id".to_string());
+        assert!(config.policy().is_err());
+    }
+}
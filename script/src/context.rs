@@ -0,0 +1,137 @@
+//! The prover client and the program's keys, set up once per process.
+//!
+//! Setting up the keys of the program takes seconds to minutes with the local prover. The keys
+//! only depend on the ELF, the SP1 version and the prover, so they can be cached on disk, keyed
+//! by them, and reused by later runs. A cache that cannot be read is set up again.
+
+use std::{
+    path::{Path, PathBuf},
+    sync::OnceLock,
+};
+
+use alloy_primitives::B256;
+use anyhow::{Context, Result};
+use log::{info, warn};
+use sha2::{Digest, Sha256};
+use sp1_sdk::{ProverClient, SP1ProvingKey, SP1VerifyingKey};
+
+use crate::SP1_PAY_ELF;
+
+/// Version of `sp1-sdk` the crate is built with, whose key format cached keys must match.
+const SP1_SDK_VERSION: &str = env!("SP1_SDK_VERSION");
+
+pub struct ProverContext {
+    client: ProverClient,
+    /// `SP1_PROVER`, the kind of prover `client` is.
+    prover: String,
+    cache_dir: Option<PathBuf>,
+    keys: OnceLock<(SP1ProvingKey, SP1VerifyingKey)>,
+}
+
+impl ProverContext {
+    /// A context for the prover selected by `SP1_PROVER`. The keys are set up on first use, and
+    /// cached in `cache_dir` if given.
+    pub fn new(cache_dir: Option<PathBuf>) -> Self {
+        Self {
+            client: ProverClient::new(),
+            prover: std::env::var("SP1_PROVER")
+                .unwrap_or_else(|_| "local".to_string())
+                .to_lowercase(),
+            cache_dir,
+            keys: OnceLock::new(),
+        }
+    }
+
+    pub fn client(&self) -> &ProverClient {
+        &self.client
+    }
+
+    /// The proving key. Sets the keys up on first use, which blocks.
+    pub fn pk(&self) -> &SP1ProvingKey {
+        &self.keys().0
+    }
+
+    /// The verifying key. Sets the keys up on first use, which blocks.
+    pub fn vk(&self) -> &SP1VerifyingKey {
+        &self.keys().1
+    }
+
+    /// SHA-256 of the program's ELF, which names its cached keys with the SP1 version and prover.
+    pub fn elf_digest() -> B256 {
+        B256::from_slice(&Sha256::digest(SP1_PAY_ELF))
+    }
+
+    fn keys(&self) -> &(SP1ProvingKey, SP1VerifyingKey) {
+        self.keys.get_or_init(|| {
+            let Some(path) = self.cache_path() else {
+                return self.client.setup(SP1_PAY_ELF);
+            };
+
+            // Keys that fail to decode, e.g. cached by another version, are a miss and replaced.
+            if path.exists() {
+                match load_keys(&path) {
+                    Ok(keys) => return keys,
+                    Err(err) => warn!("Ignoring cached keys: {err:#}"),
+                }
+            }
+            let keys = self.client.setup(SP1_PAY_ELF);
+            match save_keys(&path, &keys) {
+                Ok(()) => info!("Cached the program's keys in {}", path.display()),
+                Err(err) => warn!("Failed to cache the program's keys: {err:#}"),
+            }
+            keys
+        })
+    }
+
+    fn cache_path(&self) -> Option<PathBuf> {
+        let name = cache_file_name(&Self::elf_digest(), SP1_SDK_VERSION, &self.prover);
+        self.cache_dir.as_ref().map(|dir| dir.join(name))
+    }
+}
+
+/// Name of the cached keys of the ELF with `digest`, set up by `prover` of `sp1-sdk` `version`.
+fn cache_file_name(digest: &B256, version: &str, prover: &str) -> String {
+    format!("sp1-pay-{version}-{prover}-{}.keys", hex::encode(digest))
+}
+
+fn load_keys(path: &Path) -> Result<(SP1ProvingKey, SP1VerifyingKey)> {
+    let bytes = std::fs::read(path).with_context(|| format!("reading {}", path.display()))?;
+    bincode::deserialize(&bytes).with_context(|| format!("decoding {}", path.display()))
+}
+
+fn save_keys(path: &Path, keys: &(SP1ProvingKey, SP1VerifyingKey)) -> Result<()> {
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir)?;
+    }
+
+    // Write to a temporary file first, so a concurrent run never reads a partial cache.
+    let partial = path.with_extension("partial");
+    std::fs::write(&partial, bincode::serialize(keys)?)
+        .with_context(|| format!("writing {}", partial.display()))?;
+    std::fs::rename(&partial, path).with_context(|| format!("writing {}", path.display()))
+}
+
+#[cfg(test)]
+mod test_context {
+    use alloy_primitives::B256;
+
+    use super::{cache_file_name, load_keys};
+
+    #[test]
+    fn test_cache_file_name() {
+        let digest = B256::repeat_byte(0xab);
+        let name = cache_file_name(&digest, "1.1.1", "mock");
+        assert_eq!(name, format!("sp1-pay-1.1.1-mock-{}.keys", "ab".repeat(32)));
+        assert_ne!(name, cache_file_name(&digest, "1.1.1", "local"));
+        assert_ne!(name, cache_file_name(&digest, "1.2.0", "mock"));
+    }
+
+    #[test]
+    fn test_undecodable_keys_fail_to_load() {
+        let path = std::env::temp_dir().join(format!("sp1-pay-test-{}.keys", std::process::id()));
+        std::fs::write(&path, b"not keys").unwrap();
+        let loaded = load_keys(&path);
+        std::fs::remove_file(&path).unwrap();
+        assert!(loaded.is_err());
+    }
+}
@@ -0,0 +1,279 @@
+//! Deposits claimable by an account.
+//!
+//! The claim ID is derived with `ClaimScheme::derive`, as the program derives it from the
+//! claimant's token, so a deposit made here is claimable with a proof of that token. Bulk
+//! deposits are read from a CSV file with `recipient` and `amount` columns, and optional `key`
+//! and `salt` columns. Fields are not quoted, and rows with quotes are rejected rather than
+//! split at commas inside them.
+
+use std::{fs, path::Path};
+
+use alloy_primitives::{Address, B256, U256};
+use alloy_sol_types::SolCall;
+use anyhow::{anyhow, bail, Context, Result};
+use common::{contract::IBonsaiPay, ClaimIdentity, ClaimKey, ClaimScheme};
+use ethers::types::H256;
+
+use crate::{preflight::view, salt::parse_salt, tx::TxSender};
+
+/// Who a deposit is claimable by.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DepositRecipient {
+    pub claim_key: ClaimKey,
+    /// The email address, or the subject with `ClaimKey::Subject`.
+    pub identity: String,
+    pub salt: Option<B256>,
+}
+
+impl DepositRecipient {
+    /// The claim ID of the recipient, for tokens issued by `issuer`, and its scheme.
+    pub fn claim_id(&self, issuer: &str) -> (ClaimScheme, B256) {
+        let identity = match self.claim_key {
+            ClaimKey::Subject => ClaimIdentity {
+                issuer,
+                subject: &self.identity,
+                identifier: "",
+            },
+            ClaimKey::Identifier
+            | ClaimKey::NormalizedIdentifier
+            | ClaimKey::GmailFoldedIdentifier => ClaimIdentity {
+                issuer,
+                subject: "",
+                identifier: &self.identity,
+            },
+        };
+        ClaimScheme::derive(self.claim_key, &identity, self.salt.as_ref())
+    }
+}
+
+/// A deposit mined, see `deposit`.
+#[derive(Debug, Clone)]
+pub struct Deposit {
+    pub claim_id: B256,
+    pub scheme: ClaimScheme,
+    /// Unclaimed balance of the claim ID before the deposit.
+    pub previous_balance: U256,
+    pub transaction_hash: H256,
+}
+
+/// Deposits `amount` wei on `contract`, claimable by `recipient` with tokens issued by `issuer`,
+/// and waits for the deposit to be mined.
+pub async fn deposit(
+    sender: &TxSender,
+    contract: Address,
+    issuer: &str,
+    recipient: &DepositRecipient,
+    amount: U256,
+) -> Result<Deposit> {
+    if amount.is_zero() {
+        bail!("deposit amount must not be zero");
+    }
+    let (scheme, claim_id) = recipient.claim_id(issuer);
+    let previous_balance = view(
+        sender.provider(),
+        contract,
+        IBonsaiPay::balanceOfCall { claimId: claim_id },
+    )
+    .await?
+    ._0;
+
+    let calldata = IBonsaiPay::depositCall { claimId: claim_id }.abi_encode();
+    let transaction_hash = sender
+        .send_value(
+            contract.into_array().into(),
+            calldata.into(),
+            ethers::types::U256::from_big_endian(&amount.to_be_bytes::<32>()),
+        )
+        .await?;
+    sender.wait(transaction_hash).await?;
+
+    Ok(Deposit {
+        claim_id,
+        scheme,
+        previous_balance,
+        transaction_hash,
+    })
+}
+
+/// A deposit of a bulk deposit file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DepositRow {
+    /// Line of the row in the file, counting from 1.
+    pub line: usize,
+    pub recipient: DepositRecipient,
+    pub amount: U256,
+}
+
+/// Reads the deposits of the CSV file at `path`, see `parse_deposits`.
+pub fn read_deposits(path: &Path) -> Result<Vec<DepositRow>> {
+    let csv = fs::read_to_string(path).with_context(|| format!("reading {}", path.display()))?;
+    parse_deposits(&csv).with_context(|| format!("parsing {}", path.display()))
+}
+
+/// Parses deposits from CSV with a header row. The `recipient` column holds email addresses or
+/// subjects, and `amount` amounts in wei. `key` is `identifier` (the default), `subject`,
+/// `normalized-identifier` or `gmail-folded-identifier`, see `ClaimKey`, and `salt` a salt shared
+/// with the recipient. Blank lines and lines starting with `#` are skipped, and quoted fields are
+/// not supported.
+pub fn parse_deposits(csv: &str) -> Result<Vec<DepositRow>> {
+    let mut lines = csv
+        .lines()
+        .enumerate()
+        .map(|(index, line)| (index + 1, line.trim()))
+        .filter(|(_, line)| !line.is_empty() && !line.starts_with('#'));
+    if let Some((line, _)) = lines.clone().find(|(_, line)| line.contains('"')) {
+        bail!("line {line}: quoted fields are not supported");
+    }
+
+    let (_, header) = lines.next().ok_or_else(|| anyhow!("no header row"))?;
+    let columns: Vec<&str> = header.split(',').map(str::trim).collect();
+    let column = |name: &str| columns.iter().position(|column| *column == name);
+    let (Some(recipient), Some(amount)) = (column("recipient"), column("amount")) else {
+        bail!("the header must name the `recipient` and `amount` columns");
+    };
+    let (key, salt) = (column("key"), column("salt"));
+
+    lines
+        .map(|(line, row)| {
+            let fields: Vec<&str> = row.split(',').map(str::trim).collect();
+            if fields.len() != columns.len() {
+                bail!(
+                    "line {line}: {} fields, the header has {}",
+                    fields.len(),
+                    columns.len()
+                );
+            }
+            let field = |index: Option<usize>| index.map_or("", |index| fields[index]);
+
+            let identity = fields[recipient];
+            if identity.is_empty() {
+                bail!("line {line}: no recipient");
+            }
+            let amount: U256 = fields[amount]
+                .parse()
+                .map_err(|err| anyhow!("line {line}: invalid amount: {err}"))?;
+            if amount.is_zero() {
+                bail!("line {line}: zero amount");
+            }
+            let claim_key = parse_claim_key(field(key))
+                .ok_or_else(|| anyhow!("line {line}: unknown key `{}`", field(key)))?;
+            let salt = match field(salt) {
+                "" => None,
+                salt => Some(parse_salt(salt).with_context(|| format!("line {line}"))?),
+            };
+
+            Ok(DepositRow {
+                line,
+                recipient: DepositRecipient {
+                    claim_key,
+                    identity: identity.to_string(),
+                    salt,
+                },
+                amount,
+            })
+        })
+        .collect()
+}
+
+fn parse_claim_key(key: &str) -> Option<ClaimKey> {
+    match key {
+        "" | "identifier" => Some(ClaimKey::Identifier),
+        "subject" => Some(ClaimKey::Subject),
+        "normalized-identifier" => Some(ClaimKey::NormalizedIdentifier),
+        "gmail-folded-identifier" => Some(ClaimKey::GmailFoldedIdentifier),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod test_deposit {
+    use alloy_primitives::{B256, U256};
+    use common::{ClaimKey, ClaimScheme, GOOGLE_ISSUERS};
+    use sha2::{Digest, Sha256};
+
+    use super::{parse_deposits, DepositRecipient};
+
+    #[test]
+    fn test_claim_id() {
+        let recipient = DepositRecipient {
+            claim_key: ClaimKey::Identifier,
+            identity: "Test@Example.com".to_string(),
+            salt: None,
+        };
+        let (scheme, claim_id) = recipient.claim_id(GOOGLE_ISSUERS[0]);
+        assert_eq!(scheme, ClaimScheme::IdentifierSha256);
+        assert_eq!(
+            claim_id.as_slice(),
+            Sha256::digest(b"Test@Example.com").as_slice()
+        );
+
+        let normalized = DepositRecipient {
+            claim_key: ClaimKey::NormalizedIdentifier,
+            ..recipient.clone()
+        };
+        let (scheme, normalized_id) = normalized.claim_id(GOOGLE_ISSUERS[0]);
+        assert_eq!(scheme, ClaimScheme::NormalizedIdentifierSha256);
+        assert_eq!(
+            normalized_id.as_slice(),
+            Sha256::digest(b"test@example.com").as_slice()
+        );
+
+        let salted = DepositRecipient {
+            salt: Some(B256::repeat_byte(0x5a)),
+            ..recipient
+        };
+        assert_eq!(
+            salted.claim_id(GOOGLE_ISSUERS[0]).0,
+            ClaimScheme::SaltedIdentifierSha256
+        );
+        assert_ne!(salted.claim_id(GOOGLE_ISSUERS[0]).1, claim_id);
+    }
+
+    #[test]
+    fn test_parse_deposits() {
+        let csv = "\
+            # Payroll, March\n\
+            recipient, amount, key, salt\n\
+            alice@example.com, 1000000000000000000, ,\n\
+            \n\
+            1234567890, 5, subject, 0x5a5a5a5a5a5a5a5a5a5a5a5a5a5a5a5a5a5a5a5a5a5a5a5a5a5a5a5a5a5a5a5a\n\
+            bob@gmail.com, 0x10, gmail-folded-identifier,\n\
+            Carol@Example.com, 7, normalized-identifier,\n";
+        let rows = parse_deposits(csv).unwrap();
+
+        assert_eq!(rows.len(), 4);
+        assert_eq!(rows[0].line, 3);
+        assert_eq!(rows[0].recipient.identity, "alice@example.com");
+        assert_eq!(rows[0].recipient.claim_key, ClaimKey::Identifier);
+        assert_eq!(rows[0].amount, U256::from(1_000_000_000_000_000_000u64));
+        assert_eq!(rows[1].line, 5);
+        assert_eq!(rows[1].recipient.claim_key, ClaimKey::Subject);
+        assert_eq!(rows[1].recipient.salt, Some(B256::repeat_byte(0x5a)));
+        assert_eq!(rows[2].recipient.claim_key, ClaimKey::GmailFoldedIdentifier);
+        assert_eq!(rows[2].amount, U256::from(16));
+        assert_eq!(rows[3].recipient.claim_key, ClaimKey::NormalizedIdentifier);
+    }
+
+    #[test]
+    fn test_parse_invalid_deposits() {
+        for (csv, error) in [
+            ("", "no header row"),
+            ("email,amount\na@x.io,1", "`recipient` and `amount`"),
+            ("recipient,amount\na@x.io", "line 2: 1 fields"),
+            ("recipient,amount\na@x.io,one", "line 2: invalid amount"),
+            ("recipient,amount\na@x.io,0", "line 2: zero amount"),
+            (
+                "recipient,amount,key\na@x.io,1,email",
+                "line 2: unknown key",
+            ),
+            ("recipient,amount\n,1", "line 2: no recipient"),
+            (
+                "recipient,amount\n\"Doe, Jane\",1",
+                "line 2: quoted fields are not supported",
+            ),
+        ] {
+            let err = parse_deposits(csv).unwrap_err();
+            assert!(format!("{err:#}").contains(error), "{err:#}");
+        }
+    }
+}
@@ -0,0 +1,446 @@
+//! Events of `BonsaiPay` indexed from the chain, see `indexer`.
+//!
+//! Events are stored with the block they were emitted in, and the hashes of the latest indexed
+//! blocks are kept so that the indexer can drop the events of blocks a reorg removed. Amounts
+//! are stored as decimal strings, as wei overflow SQLite's integers, and summed here.
+
+use std::{path::Path, sync::Mutex};
+
+use alloy_primitives::{Address, B256, U256};
+use anyhow::{anyhow, Context, Result};
+use common::contract::IBonsaiPayEvents;
+use ethers::types::{Log, H256};
+use rusqlite::{params, Connection, OptionalExtension, Row};
+use serde::Serialize;
+
+use crate::claim::contract_event;
+
+/// Blocks below the last indexed one that may still be reorganized. Older blocks are final,
+/// and their hashes are not kept.
+pub const MAX_REORG_DEPTH: u64 = 128;
+
+#[derive(Serialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum EventKind {
+    Deposited,
+    Claimed,
+    RelayerPaid,
+}
+
+impl EventKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Deposited => "deposited",
+            Self::Claimed => "claimed",
+            Self::RelayerPaid => "relayerPaid",
+        }
+    }
+
+    pub fn parse(kind: &str) -> Option<Self> {
+        Some(match kind {
+            "deposited" => Self::Deposited,
+            "claimed" => Self::Claimed,
+            "relayerPaid" => Self::RelayerPaid,
+            _ => return None,
+        })
+    }
+}
+
+/// A `Deposited`, `Claimed` or `RelayerPaid` event.
+#[derive(Serialize, Debug, Clone, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct ContractEvent {
+    pub kind: EventKind,
+    pub claim_id: B256,
+    /// The recipient of a claim, or the relayer paid.
+    pub account: Option<Address>,
+    /// The amount deposited or claimed, or the relayer's fee.
+    pub amount: U256,
+    pub block_number: u64,
+    pub block_hash: H256,
+    pub transaction_hash: H256,
+    pub log_index: u64,
+}
+
+impl ContractEvent {
+    /// The event of a mined `log` of the contract, or `None` for its other events.
+    pub fn from_log(log: &Log) -> Option<Self> {
+        if log.removed == Some(true) {
+            return None;
+        }
+        let (kind, claim_id, account, amount) = match contract_event(log)? {
+            IBonsaiPayEvents::Deposited(event) => {
+                (EventKind::Deposited, event.claimId, None, event.amount)
+            }
+            IBonsaiPayEvents::Claimed(event) => (
+                EventKind::Claimed,
+                event.claimId,
+                Some(event.recipient),
+                event.amount,
+            ),
+            IBonsaiPayEvents::RelayerPaid(event) => (
+                EventKind::RelayerPaid,
+                event.claimId,
+                Some(event.relayer),
+                event.fee,
+            ),
+            _ => return None,
+        };
+
+        Some(Self {
+            kind,
+            claim_id,
+            account,
+            amount,
+            block_number: log.block_number?.as_u64(),
+            block_hash: log.block_hash?,
+            transaction_hash: log.transaction_hash?,
+            log_index: log.log_index?.as_u64(),
+        })
+    }
+}
+
+/// What was deposited to and claimed from a claim ID, or from all of them.
+#[derive(Serialize, Debug, Clone, Default, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct Balances {
+    pub deposited: U256,
+    /// Paid to the recipients, without the relayers' fees.
+    pub claimed: U256,
+    pub fees: U256,
+    /// Deposited but not claimed yet, what `balanceOf` returns.
+    pub outstanding: U256,
+}
+
+impl Balances {
+    /// The balances of a claim ID with `events`, in the order they were emitted.
+    fn of(events: &[ContractEvent]) -> Self {
+        let mut balances = Self::default();
+        for event in events {
+            match event.kind {
+                EventKind::Deposited => {
+                    balances.deposited += event.amount;
+                    balances.outstanding += event.amount;
+                }
+                // A claim pays out every pending deposit.
+                EventKind::Claimed => {
+                    balances.claimed += event.amount;
+                    balances.outstanding = U256::ZERO;
+                }
+                EventKind::RelayerPaid => balances.fees += event.amount,
+            }
+        }
+        balances
+    }
+}
+
+/// The history of a claim ID.
+#[derive(Serialize, Debug, Clone, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct ClaimHistory {
+    pub claim_id: B256,
+    #[serde(flatten)]
+    pub balances: Balances,
+    pub events: Vec<ContractEvent>,
+}
+
+/// The balances of all claim IDs.
+#[derive(Serialize, Debug, Clone, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct Totals {
+    #[serde(flatten)]
+    pub balances: Balances,
+    pub claim_ids: usize,
+    /// The last block indexed.
+    pub block_number: Option<u64>,
+}
+
+const SCHEMA: &str = "
+CREATE TABLE IF NOT EXISTS events (
+    block_number INTEGER NOT NULL,
+    log_index INTEGER NOT NULL,
+    block_hash TEXT NOT NULL,
+    transaction_hash TEXT NOT NULL,
+    kind TEXT NOT NULL,
+    claim_id TEXT NOT NULL,
+    account TEXT,
+    amount TEXT NOT NULL,
+    PRIMARY KEY (block_number, log_index)
+);
+CREATE INDEX IF NOT EXISTS events_claim_id ON events (claim_id);
+CREATE TABLE IF NOT EXISTS blocks (
+    number INTEGER PRIMARY KEY,
+    hash TEXT NOT NULL
+);
+";
+
+/// Indexed events in a SQLite database.
+pub struct EventStore {
+    connection: Mutex<Connection>,
+}
+
+impl EventStore {
+    /// Opens the database at `path`, creating it if needed.
+    pub fn open(path: &Path) -> Result<Self> {
+        let connection =
+            Connection::open(path).with_context(|| format!("opening {}", path.display()))?;
+        Self::with_connection(connection)
+    }
+
+    /// A database that only lives as long as the store.
+    pub fn in_memory() -> Result<Self> {
+        Self::with_connection(Connection::open_in_memory()?)
+    }
+
+    fn with_connection(connection: Connection) -> Result<Self> {
+        connection.execute_batch(SCHEMA)?;
+        Ok(Self {
+            connection: Mutex::new(connection),
+        })
+    }
+
+    /// The last block indexed, and its hash.
+    pub fn cursor(&self) -> Result<Option<(u64, H256)>> {
+        self.connection
+            .lock()
+            .unwrap()
+            .query_row(
+                "SELECT number, hash FROM blocks ORDER BY number DESC LIMIT 1",
+                [],
+                read_block,
+            )
+            .optional()?
+            .transpose()
+    }
+
+    /// The kept blocks, newest first.
+    pub fn blocks(&self) -> Result<Vec<(u64, H256)>> {
+        let connection = self.connection.lock().unwrap();
+        let mut statement =
+            connection.prepare("SELECT number, hash FROM blocks ORDER BY number DESC")?;
+        let blocks = statement
+            .query_map([], read_block)?
+            .map(|block| block?)
+            .collect::<Result<Vec<_>>>();
+        blocks
+    }
+
+    /// Stores the `events` of the blocks up to `block`, a block number and its hash. The hashes
+    /// of `block` and of the blocks with events are kept to find where a reorg forked.
+    pub fn insert(&self, events: &[ContractEvent], block: (u64, H256)) -> Result<()> {
+        let mut connection = self.connection.lock().unwrap();
+        let transaction = connection.transaction()?;
+        for event in events {
+            transaction.execute(
+                "INSERT OR REPLACE INTO events
+                 (block_number, log_index, block_hash, transaction_hash, kind, claim_id, account, amount)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
+                params![
+                    event.block_number,
+                    event.log_index,
+                    format!("{:?}", event.block_hash),
+                    format!("{:?}", event.transaction_hash),
+                    event.kind.as_str(),
+                    event.claim_id.to_string(),
+                    event.account.map(|account| account.to_string()),
+                    event.amount.to_string(),
+                ],
+            )?;
+        }
+
+        let blocks = events
+            .iter()
+            .map(|event| (event.block_number, event.block_hash))
+            .chain([block]);
+        for (number, hash) in blocks {
+            transaction.execute(
+                "INSERT OR REPLACE INTO blocks (number, hash) VALUES (?1, ?2)",
+                params![number, format!("{hash:?}")],
+            )?;
+        }
+        let (number, _) = block;
+        transaction.execute(
+            "DELETE FROM blocks WHERE number + ?1 < ?2",
+            params![MAX_REORG_DEPTH, number],
+        )?;
+        transaction.commit()?;
+        Ok(())
+    }
+
+    /// Drops the events and blocks after block `number`, removed from the chain by a reorg.
+    pub fn rewind(&self, number: u64) -> Result<()> {
+        let mut connection = self.connection.lock().unwrap();
+        let transaction = connection.transaction()?;
+        transaction.execute("DELETE FROM events WHERE block_number > ?1", [number])?;
+        transaction.execute("DELETE FROM blocks WHERE number > ?1", [number])?;
+        transaction.commit()?;
+        Ok(())
+    }
+
+    /// The events of `claim_id` and its balances.
+    pub fn history(&self, claim_id: B256) -> Result<ClaimHistory> {
+        let events = self.events(
+            "SELECT * FROM events WHERE claim_id = ?1 ORDER BY block_number, log_index",
+            params![claim_id.to_string()],
+        )?;
+        Ok(ClaimHistory {
+            claim_id,
+            balances: Balances::of(&events),
+            events,
+        })
+    }
+
+    /// The balances of all claim IDs.
+    pub fn totals(&self) -> Result<Totals> {
+        let events = self.events(
+            "SELECT * FROM events ORDER BY claim_id, block_number, log_index",
+            [],
+        )?;
+
+        let mut totals = Balances::default();
+        let mut claim_ids = 0;
+        for claim in events.chunk_by(|a, b| a.claim_id == b.claim_id) {
+            let balances = Balances::of(claim);
+            totals.deposited += balances.deposited;
+            totals.claimed += balances.claimed;
+            totals.fees += balances.fees;
+            totals.outstanding += balances.outstanding;
+            claim_ids += 1;
+        }
+
+        Ok(Totals {
+            balances: totals,
+            claim_ids,
+            block_number: self.cursor()?.map(|(number, _)| number),
+        })
+    }
+
+    fn events(&self, sql: &str, params: impl rusqlite::Params) -> Result<Vec<ContractEvent>> {
+        let connection = self.connection.lock().unwrap();
+        let mut statement = connection.prepare(sql)?;
+        let events = statement
+            .query_map(params, read_event)?
+            .map(|event| event?)
+            .collect::<Result<Vec<_>>>();
+        events
+    }
+}
+
+fn read_block(row: &Row) -> rusqlite::Result<Result<(u64, H256)>> {
+    let number: u64 = row.get("number")?;
+    let hash: String = row.get("hash")?;
+    Ok(hash
+        .parse()
+        .map(|hash| (number, hash))
+        .map_err(|err| anyhow!("invalid block hash {hash}: {err}")))
+}
+
+fn read_event(row: &Row) -> rusqlite::Result<Result<ContractEvent>> {
+    let kind: String = row.get("kind")?;
+    let claim_id: String = row.get("claim_id")?;
+    let account: Option<String> = row.get("account")?;
+    let amount: String = row.get("amount")?;
+    let block_number: u64 = row.get("block_number")?;
+    let block_hash: String = row.get("block_hash")?;
+    let transaction_hash: String = row.get("transaction_hash")?;
+    let log_index: u64 = row.get("log_index")?;
+
+    let event = || -> Result<ContractEvent> {
+        Ok(ContractEvent {
+            kind: EventKind::parse(&kind).ok_or_else(|| anyhow!("unknown event kind {kind}"))?,
+            claim_id: claim_id.parse()?,
+            account: account.map(|account| account.parse()).transpose()?,
+            amount: amount.parse()?,
+            block_number,
+            block_hash: block_hash.parse()?,
+            transaction_hash: transaction_hash.parse()?,
+            log_index,
+        })
+    };
+    Ok(event())
+}
+
+#[cfg(test)]
+mod test_events {
+    use alloy_primitives::{Address, B256, U256};
+    use ethers::types::H256;
+
+    use super::{ContractEvent, EventKind, EventStore, MAX_REORG_DEPTH};
+
+    fn event(kind: EventKind, claim_id: u8, amount: u64, block_number: u64) -> ContractEvent {
+        ContractEvent {
+            kind,
+            claim_id: B256::repeat_byte(claim_id),
+            account: (kind != EventKind::Deposited).then(|| Address::repeat_byte(0x11)),
+            amount: U256::from(amount),
+            block_number,
+            block_hash: H256::from_low_u64_be(block_number),
+            transaction_hash: H256::repeat_byte(block_number as u8),
+            log_index: kind as u64,
+        }
+    }
+
+    #[test]
+    fn test_history() {
+        let store = EventStore::in_memory().unwrap();
+        let events = [
+            event(EventKind::Deposited, 0xa, 100, 1),
+            event(EventKind::Deposited, 0xa, 50, 2),
+            event(EventKind::RelayerPaid, 0xa, 10, 3),
+            event(EventKind::Claimed, 0xa, 140, 3),
+            event(EventKind::Deposited, 0xa, 30, 4),
+            event(EventKind::Deposited, 0xb, 7, 4),
+        ];
+        store
+            .insert(&events, (5, H256::from_low_u64_be(5)))
+            .unwrap();
+
+        let history = store.history(B256::repeat_byte(0xa)).unwrap();
+        assert_eq!(history.events, events[..5]);
+        assert_eq!(history.balances.deposited, U256::from(180));
+        assert_eq!(history.balances.claimed, U256::from(140));
+        assert_eq!(history.balances.fees, U256::from(10));
+        assert_eq!(history.balances.outstanding, U256::from(30));
+
+        let totals = store.totals().unwrap();
+        assert_eq!(totals.claim_ids, 2);
+        assert_eq!(totals.balances.deposited, U256::from(187));
+        assert_eq!(totals.balances.outstanding, U256::from(37));
+        assert_eq!(totals.block_number, Some(5));
+
+        let unknown = store.history(B256::repeat_byte(0xc)).unwrap();
+        assert!(unknown.events.is_empty());
+        assert_eq!(unknown.balances.outstanding, U256::ZERO);
+    }
+
+    #[test]
+    fn test_rewind() {
+        let store = EventStore::in_memory().unwrap();
+        for number in 1..=3 {
+            let events = [event(EventKind::Deposited, 0xa, 100, number)];
+            store
+                .insert(&events, (number, H256::from_low_u64_be(number)))
+                .unwrap();
+        }
+
+        store.rewind(1).unwrap();
+        assert_eq!(store.cursor().unwrap(), Some((1, H256::from_low_u64_be(1))));
+        let history = store.history(B256::repeat_byte(0xa)).unwrap();
+        assert_eq!(history.events.len(), 1);
+        assert_eq!(history.balances.outstanding, U256::from(100));
+    }
+
+    #[test]
+    fn test_old_blocks_are_pruned() {
+        let store = EventStore::in_memory().unwrap();
+        store.insert(&[], (1, H256::from_low_u64_be(1))).unwrap();
+        store
+            .insert(&[], (MAX_REORG_DEPTH + 2, H256::from_low_u64_be(2)))
+            .unwrap();
+
+        assert_eq!(
+            store.blocks().unwrap(),
+            vec![(MAX_REORG_DEPTH + 2, H256::from_low_u64_be(2))]
+        );
+    }
+}
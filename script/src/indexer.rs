@@ -0,0 +1,232 @@
+//! Follows the `Deposited`, `Claimed` and `RelayerPaid` events of `BonsaiPay` into an
+//! `EventStore`, and serves per-claim histories and totals from it.
+//!
+//! Each poll first checks that the last indexed block is still on the chain. If a reorg replaced
+//! it, the newest indexed block still on the chain is found and everything after it is dropped
+//! before indexing again. Blocks within `confirmations` of the head are left for later polls.
+
+use std::{convert::Infallible, net::SocketAddr, sync::Arc, time::Duration};
+
+use alloy_primitives::{Address, B256};
+use alloy_sol_types::SolEvent;
+use anyhow::{anyhow, Context, Result};
+use common::contract::IBonsaiPay;
+use ethers::{
+    providers::{Http, Middleware, Provider},
+    types::{Filter, H256},
+};
+use log::{error, info, warn};
+use serde_json::json;
+use warp::{http::StatusCode, Filter as _, Reply};
+
+use crate::events::{ContractEvent, EventStore, MAX_REORG_DEPTH};
+
+pub const DEFAULT_POLL_INTERVAL: Duration = Duration::from_secs(5);
+
+/// Blocks whose logs are fetched in one request.
+const BATCH_SIZE: u64 = 1_000;
+
+/// Indexes the events of a contract.
+pub struct Indexer {
+    provider: Provider<Http>,
+    contract: Address,
+    store: Arc<EventStore>,
+    start_block: u64,
+    confirmations: u64,
+}
+
+impl Indexer {
+    pub fn new(rpc_url: &str, contract: Address, store: Arc<EventStore>) -> Result<Self> {
+        Ok(Self {
+            provider: Provider::<Http>::try_from(rpc_url)?,
+            contract,
+            store,
+            start_block: 0,
+            confirmations: 0,
+        })
+    }
+
+    /// Starts indexing at `block`, such as the block the contract was deployed in, when the store
+    /// is empty.
+    pub fn with_start_block(mut self, block: u64) -> Self {
+        self.start_block = block;
+        self
+    }
+
+    /// Only indexes blocks with at least `confirmations` blocks on top of them.
+    pub fn with_confirmations(mut self, confirmations: u64) -> Self {
+        self.confirmations = confirmations;
+        self
+    }
+
+    pub fn store(&self) -> &Arc<EventStore> {
+        &self.store
+    }
+
+    /// Indexes the blocks mined since the last poll and returns the last block indexed.
+    pub async fn poll(&self) -> Result<Option<u64>> {
+        let head = self.provider.get_block_number().await?.as_u64();
+        let cursor = self.store.cursor()?;
+        let Some(last) = head.checked_sub(self.confirmations) else {
+            return Ok(cursor.map(|(number, _)| number));
+        };
+
+        let mut next = match cursor {
+            Some((number, hash)) if self.block_hash(number).await? == Some(hash) => number + 1,
+            Some(_) => self.unwind().await? + 1,
+            None => self.start_block,
+        };
+        while next <= last {
+            let to = last.min(next + BATCH_SIZE - 1);
+            let hash = self
+                .block_hash(to)
+                .await?
+                .ok_or_else(|| anyhow!("block {to} not found"))?;
+            let logs = self
+                .provider
+                .get_logs(&self.filter(next, to))
+                .await
+                .with_context(|| format!("fetching the logs of blocks {next} to {to}"))?;
+            // The logs may come from another chain than `hash` if a reorg happened in between,
+            // which the next poll recovers from.
+            if self.block_hash(to).await? != Some(hash) {
+                warn!("Block {to} was reorged while indexing it");
+                break;
+            }
+
+            let events: Vec<ContractEvent> =
+                logs.iter().filter_map(ContractEvent::from_log).collect();
+            self.store.insert(&events, (to, hash))?;
+            if !events.is_empty() {
+                info!("Indexed {} events up to block {to}", events.len());
+            }
+            next = to + 1;
+        }
+
+        Ok(self.store.cursor()?.map(|(number, _)| number))
+    }
+
+    /// Polls every `interval` until the process is stopped.
+    pub async fn run(&self, interval: Duration) {
+        loop {
+            if let Err(err) = self.poll().await {
+                error!("Indexing failed: {err:#}");
+            }
+            tokio::time::sleep(interval).await;
+        }
+    }
+
+    /// Drops the blocks a reorg removed, returning the newest indexed block still on the chain.
+    /// Without a kept block on the chain, the blocks that may have been reorganized are dropped.
+    async fn unwind(&self) -> Result<u64> {
+        let blocks = self.store.blocks()?;
+        let mut ancestor = blocks
+            .first()
+            .map_or(0, |(number, _)| number.saturating_sub(MAX_REORG_DEPTH));
+        for (number, hash) in blocks {
+            if self.block_hash(number).await? == Some(hash) {
+                ancestor = number;
+                break;
+            }
+        }
+
+        warn!("Chain reorganized, dropping the events after block {ancestor}");
+        self.store.rewind(ancestor)?;
+        Ok(ancestor)
+    }
+
+    async fn block_hash(&self, number: u64) -> Result<Option<H256>> {
+        Ok(self
+            .provider
+            .get_block(number)
+            .await?
+            .and_then(|block| block.hash))
+    }
+
+    fn filter(&self, from: u64, to: u64) -> Filter {
+        let signatures = [
+            IBonsaiPay::Deposited::SIGNATURE_HASH,
+            IBonsaiPay::Claimed::SIGNATURE_HASH,
+            IBonsaiPay::RelayerPaid::SIGNATURE_HASH,
+        ];
+        Filter::new()
+            .address(ethers::types::Address::from(self.contract.into_array()))
+            .from_block(from)
+            .to_block(to)
+            .topic0(
+                signatures
+                    .iter()
+                    .map(|hash| H256(hash.0))
+                    .collect::<Vec<_>>(),
+            )
+    }
+}
+
+/// The indexer's routes, `GET /claims/{claimId}` and `GET /totals`.
+pub fn routes(
+    store: Arc<EventStore>,
+) -> impl warp::Filter<Extract = (impl Reply,), Error = warp::Rejection> + Clone {
+    let cors = warp::cors()
+        .allow_any_origin()
+        .allow_methods(vec!["GET"])
+        .max_age(3600);
+    let store = warp::any().map(move || store.clone());
+
+    let claim = warp::path!("claims" / String)
+        .and(warp::get())
+        .and(store.clone())
+        .and_then(handle_claim_history);
+    let totals = warp::path!("totals")
+        .and(warp::get())
+        .and(store)
+        .and_then(handle_totals);
+
+    claim.or(totals).with(cors)
+}
+
+/// Indexes every `interval` and serves the routes on `addr` until the process is stopped.
+pub async fn serve(indexer: Indexer, addr: SocketAddr, interval: Duration) -> Result<()> {
+    // Fail on startup if the node is unreachable.
+    indexer.poll().await?;
+
+    let store = indexer.store().clone();
+    tokio::spawn(async move { indexer.run(interval).await });
+
+    info!("Listening on {addr}");
+    warp::serve(routes(store)).run(addr).await;
+
+    Ok(())
+}
+
+async fn handle_claim_history(
+    claim_id: String,
+    store: Arc<EventStore>,
+) -> Result<warp::reply::Response, Infallible> {
+    let Ok(claim_id) = claim_id.parse::<B256>() else {
+        return Ok(error_reply(
+            &format!("invalid claim ID {claim_id}"),
+            StatusCode::BAD_REQUEST,
+        ));
+    };
+    Ok(match store.history(claim_id) {
+        Ok(history) => warp::reply::json(&history).into_response(),
+        Err(err) => internal_error(err),
+    })
+}
+
+async fn handle_totals(store: Arc<EventStore>) -> Result<warp::reply::Response, Infallible> {
+    Ok(match store.totals() {
+        Ok(totals) => warp::reply::json(&totals).into_response(),
+        Err(err) => internal_error(err),
+    })
+}
+
+fn internal_error(err: anyhow::Error) -> warp::reply::Response {
+    error!("{err:#}");
+    error_reply(&format!("{err:#}"), StatusCode::INTERNAL_SERVER_ERROR)
+}
+
+fn error_reply(error: &str, status: StatusCode) -> warp::reply::Response {
+    let reply = warp::reply::json(&json!({ "error": error }));
+    warp::reply::with_status(reply, status).into_response()
+}
@@ -0,0 +1,390 @@
+//! Claims submitted to the prover server, persisted so they survive restarts.
+//!
+//! A job moves through `Queued`, `Executing`, `Proving` and `Submitting` to `Done`, or to
+//! `Failed` from any of them. The calldata is stored once proved and the transaction hash once
+//! sent, so a job interrupted while submitting resumes without proving again or sending twice.
+//! The ID token, salt and calldata are only kept until the job is over.
+
+use std::{
+    path::Path,
+    sync::Mutex,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use alloy_primitives::B256;
+use anyhow::{anyhow, Context, Result};
+use common::{ClaimKey, ClaimNonce};
+use ethers::types::H256;
+use rand::{rngs::OsRng, RngCore};
+use rusqlite::{params, Connection, OptionalExtension, Row};
+use serde::Serialize;
+
+#[derive(Serialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum JobState {
+    Queued,
+    Executing,
+    Proving,
+    Submitting,
+    Done,
+    Failed,
+}
+
+impl JobState {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Queued => "queued",
+            Self::Executing => "executing",
+            Self::Proving => "proving",
+            Self::Submitting => "submitting",
+            Self::Done => "done",
+            Self::Failed => "failed",
+        }
+    }
+
+    pub fn parse(state: &str) -> Option<Self> {
+        Some(match state {
+            "queued" => Self::Queued,
+            "executing" => Self::Executing,
+            "proving" => Self::Proving,
+            "submitting" => Self::Submitting,
+            "done" => Self::Done,
+            "failed" => Self::Failed,
+            _ => return None,
+        })
+    }
+
+    /// Whether the job is over, successfully or not.
+    pub fn is_final(&self) -> bool {
+        matches!(self, Self::Done | Self::Failed)
+    }
+}
+
+/// A claim to prove and submit. Serializes to its status, without the token, salt or calldata.
+#[derive(Serialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct Job {
+    pub id: String,
+    pub state: JobState,
+    /// The ID token, cleared once the job is done or failed.
+    #[serde(skip)]
+    pub token: String,
+    pub nonce: ClaimNonce,
+    /// What the claim ID is derived from, as chosen by the depositor.
+    pub claim_key: ClaimKey,
+    /// Salt of a salted claim, see `ClaimScheme`. Cleared once the job is done or failed.
+    #[serde(skip)]
+    pub salt: Option<B256>,
+    /// Calldata of the claim transaction, from when it is proved until the job is over.
+    #[serde(skip)]
+    pub calldata: Option<Vec<u8>>,
+    pub transaction_hash: Option<H256>,
+    pub error: Option<String>,
+    pub created_at: u64,
+    pub updated_at: u64,
+}
+
+const SCHEMA: &str = "
+CREATE TABLE IF NOT EXISTS jobs (
+    id TEXT PRIMARY KEY,
+    state TEXT NOT NULL,
+    token TEXT NOT NULL,
+    nonce TEXT NOT NULL,
+    claim_key TEXT NOT NULL,
+    salt BLOB,
+    calldata BLOB,
+    transaction_hash TEXT,
+    error TEXT,
+    created_at INTEGER NOT NULL,
+    updated_at INTEGER NOT NULL
+);
+CREATE INDEX IF NOT EXISTS jobs_state ON jobs (state);
+";
+
+/// Jobs in a SQLite database.
+pub struct JobStore {
+    connection: Mutex<Connection>,
+}
+
+impl JobStore {
+    /// Opens the database at `path`, creating it if needed.
+    pub fn open(path: &Path) -> Result<Self> {
+        let connection =
+            Connection::open(path).with_context(|| format!("opening {}", path.display()))?;
+        Self::with_connection(connection)
+    }
+
+    /// A database that only lives as long as the store.
+    pub fn in_memory() -> Result<Self> {
+        Self::with_connection(Connection::open_in_memory()?)
+    }
+
+    fn with_connection(connection: Connection) -> Result<Self> {
+        connection.execute_batch(SCHEMA)?;
+        Ok(Self {
+            connection: Mutex::new(connection),
+        })
+    }
+
+    /// Queues a new job for `token`.
+    pub fn create(
+        &self,
+        token: String,
+        nonce: ClaimNonce,
+        claim_key: ClaimKey,
+        salt: Option<B256>,
+    ) -> Result<Job> {
+        let mut id = [0u8; 16];
+        OsRng.fill_bytes(&mut id);
+        let now = unix_time();
+        let job = Job {
+            id: hex::encode(id),
+            state: JobState::Queued,
+            token,
+            nonce,
+            claim_key,
+            salt,
+            calldata: None,
+            transaction_hash: None,
+            error: None,
+            created_at: now,
+            updated_at: now,
+        };
+
+        self.connection.lock().unwrap().execute(
+            "INSERT INTO jobs (id, state, token, nonce, claim_key, salt, created_at, updated_at)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?7)",
+            params![
+                job.id,
+                job.state.as_str(),
+                job.token,
+                serde_json::to_string(&job.nonce)?,
+                serde_json::to_string(&job.claim_key)?,
+                job.salt.as_ref().map(|salt| salt.as_slice()),
+                now
+            ],
+        )?;
+        Ok(job)
+    }
+
+    pub fn get(&self, id: &str) -> Result<Option<Job>> {
+        self.connection
+            .lock()
+            .unwrap()
+            .query_row("SELECT * FROM jobs WHERE id = ?1", [id], read_job)
+            .optional()?
+            .transpose()
+    }
+
+    /// Jobs that are neither done nor failed, oldest first.
+    pub fn incomplete(&self) -> Result<Vec<Job>> {
+        let connection = self.connection.lock().unwrap();
+        let mut statement = connection
+            .prepare("SELECT * FROM jobs WHERE state NOT IN ('done', 'failed') ORDER BY rowid")?;
+        let jobs = statement
+            .query_map([], read_job)?
+            .map(|job| job?)
+            .collect::<Result<Vec<_>>>();
+        jobs
+    }
+
+    pub fn set_state(&self, id: &str, state: JobState) -> Result<()> {
+        self.update(id, "state = ?2", params![id, state.as_str()])
+    }
+
+    /// Records the claim's calldata and moves the job to `Submitting`.
+    pub fn set_calldata(&self, id: &str, calldata: &[u8]) -> Result<()> {
+        self.update(
+            id,
+            "state = ?2, calldata = ?3",
+            params![id, JobState::Submitting.as_str(), calldata],
+        )
+    }
+
+    /// Records the hash of the claim transaction once it is sent, before it is mined.
+    pub fn set_transaction_hash(&self, id: &str, hash: H256) -> Result<()> {
+        self.update(
+            id,
+            "transaction_hash = ?2",
+            params![id, format!("{hash:?}")],
+        )
+    }
+
+    /// Marks the job done and forgets its token, salt and calldata, which are no longer needed.
+    pub fn finish(&self, id: &str) -> Result<()> {
+        self.update(
+            id,
+            "state = ?2, token = '', salt = NULL, calldata = NULL",
+            params![id, JobState::Done.as_str()],
+        )
+    }
+
+    /// Marks the job failed with `error` and forgets its token, salt and calldata.
+    pub fn fail(&self, id: &str, error: &str) -> Result<()> {
+        self.update(
+            id,
+            "state = ?2, error = ?3, token = '', salt = NULL, calldata = NULL",
+            params![id, JobState::Failed.as_str(), error],
+        )
+    }
+
+    fn update(&self, id: &str, set: &str, params: &[&dyn rusqlite::ToSql]) -> Result<()> {
+        let now = unix_time();
+        let mut params = params.to_vec();
+        params.push(&now);
+        let sql = format!(
+            "UPDATE jobs SET {set}, updated_at = ?{} WHERE id = ?1",
+            params.len()
+        );
+
+        match self
+            .connection
+            .lock()
+            .unwrap()
+            .execute(&sql, params.as_slice())?
+        {
+            0 => Err(anyhow!("no job {id}")),
+            _ => Ok(()),
+        }
+    }
+}
+
+fn read_job(row: &Row) -> rusqlite::Result<Result<Job>> {
+    let state: String = row.get("state")?;
+    let nonce: String = row.get("nonce")?;
+    let claim_key: String = row.get("claim_key")?;
+    let salt: Option<Vec<u8>> = row.get("salt")?;
+    let transaction_hash: Option<String> = row.get("transaction_hash")?;
+    let id: String = row.get("id")?;
+    let token: String = row.get("token")?;
+    let calldata: Option<Vec<u8>> = row.get("calldata")?;
+    let error: Option<String> = row.get("error")?;
+    let created_at: u64 = row.get("created_at")?;
+    let updated_at: u64 = row.get("updated_at")?;
+
+    let job = || -> Result<Job> {
+        Ok(Job {
+            state: JobState::parse(&state).ok_or_else(|| anyhow!("unknown job state {state}"))?,
+            nonce: serde_json::from_str(&nonce).context("parsing the job's nonce")?,
+            claim_key: serde_json::from_str(&claim_key).context("parsing the job's claim key")?,
+            salt: salt
+                .map(|salt| B256::try_from(salt.as_slice()))
+                .transpose()
+                .context("parsing the job's salt")?,
+            transaction_hash: transaction_hash.map(|hash| hash.parse()).transpose()?,
+            id,
+            token,
+            calldata,
+            error,
+            created_at,
+            updated_at,
+        })
+    };
+    Ok(job())
+}
+
+fn unix_time() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .expect("system time before UNIX epoch")
+        .as_secs()
+}
+
+#[cfg(test)]
+mod test_jobs {
+    use alloy_primitives::B256;
+    use common::{ClaimKey, ClaimNonce};
+    use ethers::types::H256;
+
+    use super::{JobState, JobStore};
+
+    #[test]
+    fn test_job_lifecycle() {
+        let store = JobStore::in_memory().unwrap();
+        let job = store
+            .create(
+                "token".to_string(),
+                ClaimNonce::default(),
+                ClaimKey::Identifier,
+                None,
+            )
+            .unwrap();
+        assert_eq!(job.state, JobState::Queued);
+
+        store.set_state(&job.id, JobState::Proving).unwrap();
+        store.set_calldata(&job.id, &[1, 2, 3]).unwrap();
+        store
+            .set_transaction_hash(&job.id, H256::repeat_byte(0xab))
+            .unwrap();
+        let submitting = store.get(&job.id).unwrap().unwrap();
+        assert_eq!(submitting.state, JobState::Submitting);
+        assert_eq!(submitting.calldata, Some(vec![1, 2, 3]));
+        assert_eq!(submitting.transaction_hash, Some(H256::repeat_byte(0xab)));
+        assert_eq!(submitting.token, "token");
+
+        store.finish(&job.id).unwrap();
+        let done = store.get(&job.id).unwrap().unwrap();
+        assert_eq!(done.state, JobState::Done);
+        assert!(done.token.is_empty());
+        assert_eq!(done.salt, None);
+        assert_eq!(done.calldata, None);
+        assert_eq!(done.transaction_hash, Some(H256::repeat_byte(0xab)));
+    }
+
+    #[test]
+    fn test_incomplete_jobs() {
+        let store = JobStore::in_memory().unwrap();
+        let jobs: Vec<_> = (0..3)
+            .map(|_| {
+                store
+                    .create(
+                        "token".to_string(),
+                        ClaimNonce::default(),
+                        ClaimKey::Identifier,
+                        None,
+                    )
+                    .unwrap()
+            })
+            .collect();
+        store.finish(&jobs[0].id).unwrap();
+        store.fail(&jobs[1].id, "rejected").unwrap();
+
+        let incomplete = store.incomplete().unwrap();
+        assert_eq!(incomplete.len(), 1);
+        assert_eq!(incomplete[0].id, jobs[2].id);
+        let failed = store.get(&jobs[1].id).unwrap().unwrap();
+        assert_eq!(failed.error.as_deref(), Some("rejected"));
+        assert!(failed.token.is_empty());
+        assert_eq!(store.get(&jobs[2].id).unwrap().unwrap().token, "token");
+    }
+
+    #[test]
+    fn test_salted_job() {
+        let store = JobStore::in_memory().unwrap();
+        let salt = B256::repeat_byte(0x5a);
+        let job = store
+            .create(
+                "token".to_string(),
+                ClaimNonce::default(),
+                ClaimKey::Subject,
+                Some(salt),
+            )
+            .unwrap();
+
+        let stored = store.get(&job.id).unwrap().unwrap();
+        assert_eq!(stored.claim_key, ClaimKey::Subject);
+        assert_eq!(stored.salt, Some(salt));
+
+        store.fail(&job.id, "rejected").unwrap();
+        let failed = store.get(&job.id).unwrap().unwrap();
+        assert_eq!(failed.claim_key, ClaimKey::Subject);
+        assert_eq!(failed.salt, None);
+    }
+
+    #[test]
+    fn test_unknown_job() {
+        let store = JobStore::in_memory().unwrap();
+        assert!(store.get("missing").unwrap().is_none());
+        assert!(store.finish("missing").is_err());
+    }
+}
@@ -0,0 +1,207 @@
+//! Tracks the signing keys an issuer publishes, so the contract's key registry can follow
+//! rotations without invalidating proofs that are still in flight.
+
+use std::path::Path;
+
+use alloy_primitives::B256;
+use anyhow::{Context, Result};
+use common::JwkKeys;
+use serde::{Deserialize, Serialize};
+
+/// How long, in seconds, a key stays registered after the issuer stopped publishing it.
+///
+/// Covers tokens signed just before the rotation (Google ID tokens live for an hour) and proofs
+/// of them (`BonsaiPay.MAX_PROOF_AGE`, an hour).
+pub const DEFAULT_GRACE_PERIOD: u64 = 2 * 60 * 60;
+
+/// A key the issuer published at some point.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct TrackedKey {
+    pub key_id: String,
+    pub digest: B256,
+    /// When the key was first published.
+    pub first_seen: u64,
+    /// When the key was first found missing from the issuer's keys, if it is.
+    pub retired_at: Option<u64>,
+}
+
+/// Changes that bring the registered keys in line with the `KeyRegistry`.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct KeyRegistryPlan {
+    /// Keys that tokens may be signed with but are not registered.
+    pub register: Vec<B256>,
+    /// Registered keys that are neither published nor in their grace period.
+    pub stale: Vec<B256>,
+}
+
+/// Current and previous keys of an issuer, persisted between runs.
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct KeyRegistry {
+    keys: Vec<TrackedKey>,
+    #[serde(skip)]
+    grace_period: u64,
+}
+
+impl KeyRegistry {
+    pub fn new(grace_period: u64) -> Self {
+        Self {
+            keys: Vec::new(),
+            grace_period,
+        }
+    }
+
+    /// Loads the registry from `path`, or starts an empty one if the file does not exist.
+    pub fn load(path: &Path, grace_period: u64) -> Result<Self> {
+        if !path.exists() {
+            return Ok(Self::new(grace_period));
+        }
+
+        let json = std::fs::read(path).with_context(|| format!("reading {}", path.display()))?;
+        let mut registry: Self =
+            serde_json::from_slice(&json).with_context(|| format!("parsing {}", path.display()))?;
+        registry.grace_period = grace_period;
+        Ok(registry)
+    }
+
+    pub fn save(&self, path: &Path) -> Result<()> {
+        let json = serde_json::to_vec_pretty(self)?;
+        std::fs::write(path, json).with_context(|| format!("writing {}", path.display()))
+    }
+
+    /// Records the keys the issuer publishes at `now`. Keys missing from `jwks` are retired, and
+    /// dropped once their grace period is over.
+    pub fn observe(&mut self, jwks: &JwkKeys, now: u64) {
+        let published: Vec<(String, B256)> = jwks
+            .keys
+            .iter()
+            .filter_map(|key| Some((key.extra.key_id.clone(), key.digest()?)))
+            .collect();
+
+        for key in &mut self.keys {
+            let is_published = published.iter().any(|(_, digest)| *digest == key.digest);
+            match (is_published, key.retired_at) {
+                (true, _) => key.retired_at = None,
+                (false, None) => key.retired_at = Some(now),
+                (false, Some(_)) => {}
+            }
+        }
+
+        for (key_id, digest) in published {
+            if !self.keys.iter().any(|key| key.digest == digest) {
+                self.keys.push(TrackedKey {
+                    key_id,
+                    digest,
+                    first_seen: now,
+                    retired_at: None,
+                });
+            }
+        }
+
+        let grace_period = self.grace_period;
+        self.keys
+            .retain(|key| key.retired_at.map_or(true, |at| now <= at + grace_period));
+    }
+
+    /// Keys the issuer currently publishes.
+    pub fn current(&self) -> impl Iterator<Item = &TrackedKey> {
+        self.keys.iter().filter(|key| key.retired_at.is_none())
+    }
+
+    /// Keys the issuer no longer publishes that are still in their grace period at `now`.
+    pub fn previous(&self, now: u64) -> impl Iterator<Item = &TrackedKey> {
+        let grace_period = self.grace_period;
+        self.keys
+            .iter()
+            .filter(move |key| key.retired_at.is_some_and(|at| now <= at + grace_period))
+    }
+
+    /// Digests that should be registered on-chain at `now`: current and previous keys.
+    pub fn digests(&self, now: u64) -> Vec<B256> {
+        self.current()
+            .chain(self.previous(now))
+            .map(|key| key.digest)
+            .collect()
+    }
+
+    /// Compares the digests `registered` on-chain with those that should be at `now`.
+    pub fn plan(&self, registered: &[B256], now: u64) -> KeyRegistryPlan {
+        let wanted = self.digests(now);
+        KeyRegistryPlan {
+            register: wanted
+                .iter()
+                .filter(|digest| !registered.contains(digest))
+                .copied()
+                .collect(),
+            stale: registered
+                .iter()
+                .filter(|digest| !wanted.contains(digest))
+                .copied()
+                .collect(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test_keys {
+    use common::JwkKeys;
+
+    use super::KeyRegistry;
+
+    const JWKS: &str = r#"
+    {
+      "keys": [
+        { "kty": "OKP", "crv": "Ed25519", "x": "11qYAYKxCrfVS_7TyWQHOg7hcvPapiMlrwIaaPcHURo", "kid": "a" },
+        { "kty": "OKP", "crv": "Ed25519", "x": "3p7bfXt9wbTTW2HC7OQ1Nz-DQ8hbeGdNrfx-FG-IK08", "kid": "b" },
+        { "kty": "OKP", "crv": "Ed25519", "x": "Ngi8oeROpsTSaOttsCJgJpiSwLQrhrvx53pvoWw8koI", "kid": "c" }
+      ]
+    }
+    "#;
+
+    fn jwks(kids: &[&str]) -> JwkKeys {
+        let mut jwks: JwkKeys = serde_json::from_str(JWKS).unwrap();
+        jwks.keys
+            .retain(|key| kids.contains(&key.extra.key_id.as_str()));
+        jwks
+    }
+
+    #[test]
+    fn test_rotation_keeps_previous_key_during_grace_period() {
+        let all = jwks(&["a", "b", "c"]);
+        let digest = |kid| all.key_digest(kid).unwrap();
+
+        let mut registry = KeyRegistry::new(100);
+        registry.observe(&jwks(&["a", "b"]), 1_000);
+        registry.observe(&jwks(&["b", "c"]), 1_050);
+
+        let plan = registry.plan(&[digest("a"), digest("b")], 1_100);
+        assert_eq!(plan.register, vec![digest("c")]);
+        assert!(plan.stale.is_empty());
+
+        let plan = registry.plan(&[digest("a"), digest("b")], 1_151);
+        assert_eq!(plan.register, vec![digest("c")]);
+        assert_eq!(plan.stale, vec![digest("a")]);
+    }
+
+    #[test]
+    fn test_republished_key_is_current_again() {
+        let mut registry = KeyRegistry::new(100);
+        registry.observe(&jwks(&["a"]), 1_000);
+        registry.observe(&jwks(&["b"]), 1_010);
+        registry.observe(&jwks(&["a", "b"]), 1_020);
+
+        assert_eq!(registry.current().count(), 2);
+        assert_eq!(registry.previous(1_020).count(), 0);
+    }
+
+    #[test]
+    fn test_expired_keys_are_dropped() {
+        let mut registry = KeyRegistry::new(100);
+        registry.observe(&jwks(&["a"]), 1_000);
+        registry.observe(&jwks(&["b"]), 1_010);
+        registry.observe(&jwks(&["b"]), 1_200);
+
+        let kids: Vec<_> = registry.current().map(|key| key.key_id.as_str()).collect();
+        assert_eq!(kids, vec!["b"]);
+        assert_eq!(registry.digests(1_200).len(), 1);
+    }
+}
@@ -1,11 +1,52 @@
+pub mod claim;
+pub mod config;
+pub mod context;
+pub mod deposit;
+pub mod events;
+pub mod indexer;
+pub mod jobs;
+pub mod keys;
+pub mod nonce;
+pub mod relayer;
+pub mod salt;
+pub mod server;
+pub mod tx;
+
+pub use common::{fixture, preflight};
+
 use anyhow::Result;
-use reqwest::get;
-use alloy_primitives::Bytes;
+use common::ProofInputs;
+use oidc_validator::{verify_claim, OidcErr};
+use sp1_sdk::{ExecutionReport, ProverClient, SP1PublicValues, SP1Stdin};
+use thiserror::Error;
+
+/// The ELF (executable and linkable format) file for the Succinct RISC-V zkVM.
+pub const SP1_PAY_ELF: &[u8] = include_bytes!("../../elf/riscv32im-succinct-zkvm-elf");
+
+#[derive(Debug, Error)]
+pub enum ExecuteError {
+    #[error("claim rejected: {0}")]
+    Rejected(#[from] OidcErr),
+    #[error("execution failed: {0}")]
+    Execution(anyhow::Error),
+}
 
-pub async fn fetch_google_jwt_cert() -> Result<Bytes, Box<dyn std::error::Error>> {
-    let url = "https://www.googleapis.com/oauth2/v3/certs";
-    let response = get(url).await?.json::<serde_json::Value>().await?;
-    let bytes = Bytes::from(serde_json::to_vec(&response).unwrap());
+/// Executes the program on `inputs` without generating a proof.
+///
+/// The program aborts on any validation error, which only surfaces as an opaque execution
+/// failure. In that case the claim is replayed natively to report the `OidcErr` that caused it.
+pub fn execute(
+    client: &ProverClient,
+    inputs: &ProofInputs,
+) -> Result<(SP1PublicValues, ExecutionReport), ExecuteError> {
+    let mut stdin = SP1Stdin::new();
+    stdin.write(inputs);
 
-    Ok(bytes)
-}
\ No newline at end of file
+    client
+        .execute(SP1_PAY_ELF, stdin)
+        .run()
+        .map_err(|err| match verify_claim(inputs) {
+            Err(rejection) => ExecuteError::Rejected(rejection),
+            Ok(_) => ExecuteError::Execution(err.into()),
+        })
+}
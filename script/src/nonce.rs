@@ -0,0 +1,42 @@
+//! Nonces to request ID tokens with, see `ClaimNonce`.
+
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use alloy_primitives::{Address, B256, U256};
+use common::ClaimNonce;
+use rand::{rngs::OsRng, RngCore};
+
+/// Default lifetime of a nonce, in seconds: time to sign in, prove and claim.
+pub const DEFAULT_NONCE_TTL: u64 = 60 * 60;
+
+/// A nonce paying `recipient` on `contract` of chain `chain_id`, valid for `ttl` seconds.
+///
+/// `fee` wei of the claimed balance go to `relayer` for submitting the claim; a zero fee with
+/// any relayer lets the prover server sponsor the claim.
+///
+/// Request the token with `nonce()` and keep the returned preimage: proving needs it.
+pub fn new_claim_nonce(
+    recipient: Address,
+    chain_id: u64,
+    contract: Address,
+    ttl: u64,
+    relayer: Address,
+    fee: U256,
+) -> ClaimNonce {
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .expect("system time before UNIX epoch")
+        .as_secs();
+    let mut random = B256::ZERO;
+    OsRng.fill_bytes(random.as_mut_slice());
+
+    ClaimNonce {
+        recipient,
+        chain_id,
+        contract,
+        expiry: now + ttl,
+        random,
+        relayer,
+        fee,
+    }
+}
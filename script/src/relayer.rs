@@ -0,0 +1,247 @@
+//! What the prover server relays, submitting claims for claimants without ETH for gas.
+//!
+//! The relayer pays the gas of the claims it submits. It may require a fee, which the claimant
+//! commits to in the token's nonce and the contract pays out of the claimed balance, see
+//! `ClaimNonce`. Rate limits per recipient and per claim ID keep a single claimant from spending
+//! the relayer's funds on proofs and transactions. They are counted in memory, so they start over
+//! when the server restarts.
+
+use std::{
+    collections::{HashMap, VecDeque},
+    hash::Hash,
+    sync::Mutex,
+};
+
+use alloy_primitives::{Address, B256, U256};
+use clap::Args;
+use common::ClaimNonce;
+use thiserror::Error;
+
+/// Default window of the rate limits, in seconds.
+pub const DEFAULT_RATE_LIMIT_WINDOW: u64 = 24 * 60 * 60;
+
+#[derive(Args, Debug, Clone)]
+pub struct RelayerArgs {
+    /// Minimum fee in wei a claim must commit to pay the relayer. Zero sponsors the gas of every
+    /// claim.
+    #[clap(long, env = "RELAYER_FEE", default_value_t = U256::ZERO)]
+    pub relayer_fee: U256,
+
+    /// Claims relayed per recipient within the rate limit window. Unlimited if unset.
+    #[clap(long, env = "RELAYER_CLAIMS_PER_RECIPIENT")]
+    pub claims_per_recipient: Option<u32>,
+
+    /// Claims relayed per claim ID within the rate limit window. Unlimited if unset.
+    #[clap(long, env = "RELAYER_CLAIMS_PER_CLAIM_ID")]
+    pub claims_per_claim_id: Option<u32>,
+
+    /// Seconds over which claims are counted for the rate limits.
+    #[clap(long, env = "RELAYER_RATE_LIMIT_WINDOW", default_value_t = DEFAULT_RATE_LIMIT_WINDOW)]
+    pub rate_limit_window: u64,
+}
+
+impl Default for RelayerArgs {
+    fn default() -> Self {
+        Self {
+            relayer_fee: U256::ZERO,
+            claims_per_recipient: None,
+            claims_per_claim_id: None,
+            rate_limit_window: DEFAULT_RATE_LIMIT_WINDOW,
+        }
+    }
+}
+
+/// A claim the relayer refuses to submit.
+#[derive(Debug, Error)]
+pub enum RelayError {
+    #[error("the nonce commits to a fee of {committed} wei, the relayer requires {required}")]
+    FeeTooLow { committed: U256, required: U256 },
+    #[error("the nonce pays its fee to relayer {0}, not this one")]
+    OtherRelayer(Address),
+    #[error("too many claims for recipient {0}, try again later")]
+    RecipientRateLimited(Address),
+    #[error("too many claims for claim ID {0}, try again later")]
+    ClaimIdRateLimited(B256),
+}
+
+pub struct Relayer {
+    address: Address,
+    fee: U256,
+    recipients: RateLimiter<Address>,
+    claim_ids: RateLimiter<B256>,
+}
+
+impl Relayer {
+    /// A relayer submitting claims from `address`.
+    pub fn new(address: Address, args: &RelayerArgs) -> Self {
+        Self {
+            address,
+            fee: args.relayer_fee,
+            recipients: RateLimiter::new(args.claims_per_recipient, args.rate_limit_window),
+            claim_ids: RateLimiter::new(args.claims_per_claim_id, args.rate_limit_window),
+        }
+    }
+
+    pub fn address(&self) -> Address {
+        self.address
+    }
+
+    /// The minimum fee in wei.
+    pub fn fee(&self) -> U256 {
+        self.fee
+    }
+
+    /// Checks that `nonce` pays the relayer's fee, and counts the claim against its recipient's
+    /// rate limit.
+    pub fn admit(&self, nonce: &ClaimNonce, now: u64) -> Result<(), RelayError> {
+        if nonce.fee < self.fee {
+            return Err(RelayError::FeeTooLow {
+                committed: nonce.fee,
+                required: self.fee,
+            });
+        }
+        // A fee paid to another relayer would be deducted for nothing.
+        if !nonce.fee.is_zero() && nonce.relayer != self.address {
+            return Err(RelayError::OtherRelayer(nonce.relayer));
+        }
+        if !self.recipients.admit(nonce.recipient, now) {
+            return Err(RelayError::RecipientRateLimited(nonce.recipient));
+        }
+        Ok(())
+    }
+
+    /// Counts a claim against the rate limit of `claim_id`, known once the token is validated.
+    pub fn admit_claim_id(&self, claim_id: B256, now: u64) -> Result<(), RelayError> {
+        match self.claim_ids.admit(claim_id, now) {
+            true => Ok(()),
+            false => Err(RelayError::ClaimIdRateLimited(claim_id)),
+        }
+    }
+}
+
+/// Admits up to `limit` hits per key within a sliding window. Hits are not persisted.
+struct RateLimiter<K> {
+    limit: Option<u32>,
+    window: u64,
+    hits: Mutex<HashMap<K, VecDeque<u64>>>,
+}
+
+impl<K: Hash + Eq> RateLimiter<K> {
+    fn new(limit: Option<u32>, window: u64) -> Self {
+        Self {
+            limit,
+            window,
+            hits: Mutex::new(HashMap::new()),
+        }
+    }
+
+    fn admit(&self, key: K, now: u64) -> bool {
+        let Some(limit) = self.limit else {
+            return true;
+        };
+
+        let mut hits = self.hits.lock().unwrap();
+        let window = self.window;
+        for times in hits.values_mut() {
+            while times.front().is_some_and(|time| time + window <= now) {
+                times.pop_front();
+            }
+        }
+        hits.retain(|_, times| !times.is_empty());
+
+        let times = hits.entry(key).or_default();
+        if times.len() >= limit as usize {
+            return false;
+        }
+        times.push_back(now);
+        true
+    }
+}
+
+#[cfg(test)]
+mod test_relayer {
+    use alloy_primitives::{Address, B256, U256};
+    use common::ClaimNonce;
+
+    use super::{RelayError, Relayer, RelayerArgs};
+
+    const RELAYER: Address = Address::repeat_byte(0xee);
+
+    fn nonce(fee: u64) -> ClaimNonce {
+        ClaimNonce {
+            recipient: Address::repeat_byte(0x11),
+            relayer: RELAYER,
+            fee: U256::from(fee),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_fee() {
+        let relayer = Relayer::new(
+            RELAYER,
+            &RelayerArgs {
+                relayer_fee: U256::from(100),
+                ..Default::default()
+            },
+        );
+
+        relayer.admit(&nonce(100), 0).unwrap();
+        assert!(matches!(
+            relayer.admit(&nonce(99), 0),
+            Err(RelayError::FeeTooLow { .. })
+        ));
+
+        let other = ClaimNonce {
+            relayer: Address::repeat_byte(0x22),
+            ..nonce(100)
+        };
+        assert!(matches!(
+            relayer.admit(&other, 0),
+            Err(RelayError::OtherRelayer(_))
+        ));
+    }
+
+    #[test]
+    fn test_sponsored_claims() {
+        let relayer = Relayer::new(RELAYER, &RelayerArgs::default());
+        let sponsored = ClaimNonce {
+            relayer: Address::ZERO,
+            ..nonce(0)
+        };
+        for _ in 0..10 {
+            relayer.admit(&sponsored, 0).unwrap();
+        }
+    }
+
+    #[test]
+    fn test_rate_limits() {
+        let relayer = Relayer::new(
+            RELAYER,
+            &RelayerArgs {
+                claims_per_recipient: Some(2),
+                claims_per_claim_id: Some(1),
+                rate_limit_window: 100,
+                ..Default::default()
+            },
+        );
+
+        relayer.admit(&nonce(0), 0).unwrap();
+        relayer.admit(&nonce(0), 50).unwrap();
+        assert!(matches!(
+            relayer.admit(&nonce(0), 99),
+            Err(RelayError::RecipientRateLimited(_))
+        ));
+        // The first claim left the window.
+        relayer.admit(&nonce(0), 100).unwrap();
+
+        let claim_id = B256::repeat_byte(0xc1);
+        relayer.admit_claim_id(claim_id, 0).unwrap();
+        assert!(matches!(
+            relayer.admit_claim_id(claim_id, 99),
+            Err(RelayError::ClaimIdRateLimited(_))
+        ));
+        relayer.admit_claim_id(B256::repeat_byte(0xc2), 99).unwrap();
+        relayer.admit_claim_id(claim_id, 100).unwrap();
+    }
+}
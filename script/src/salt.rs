@@ -0,0 +1,22 @@
+//! Salts of `ClaimScheme::SaltedIdentifierSha256` claims.
+//!
+//! The depositor generates a salt, deposits to the salted claim ID and shares the salt with the
+//! claimant out of band. The claimant passes it back as `ProofInputs::salt`.
+
+use alloy_primitives::B256;
+use anyhow::{Context, Result};
+use rand::{rngs::OsRng, RngCore};
+
+/// Generates a salt from the operating system's random number generator.
+pub fn generate_salt() -> B256 {
+    let mut salt = B256::ZERO;
+    OsRng.fill_bytes(salt.as_mut_slice());
+    salt
+}
+
+/// Parses a salt shared as 32 hex-encoded bytes, with or without a `0x` prefix.
+pub fn parse_salt(salt: &str) -> Result<B256> {
+    salt.trim()
+        .parse()
+        .context("salt must be 32 hex-encoded bytes")
+}
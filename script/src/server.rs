@@ -0,0 +1,503 @@
+//! HTTP endpoint that proves claims for ID tokens and submits them to the contract.
+//!
+//! `GET /auth` takes the ID token in `X-Auth-Token` and the JSON preimage of its nonce in
+//! `X-Claim-Nonce`. A deposit made with another claim key or a salt is claimed by also passing
+//! the key in `X-Claim-Key`, e.g. `Subject`, and the salt in `X-Claim-Salt` as 0x-prefixed hex.
+//! It validates the token, queues the claim and replies `202 Accepted` with its job ID, or
+//! `400 Bad Request` if the token is invalid, the claim is for another deployment or the relayer
+//! refuses it.
+//! `GET /jobs/{id}` replies with the job's status, see `Job`, and `GET /relayer` with the address
+//! and minimum fee to commit to in nonces, see `Relayer`.
+//!
+//! Jobs are processed one at a time by a worker, and persisted so that the worker resumes
+//! incomplete jobs on startup. Before proving, the worker checks that the contract would accept
+//! the proof, see `preflight`, and before sending it simulates the claim.
+
+use std::{
+    convert::Infallible,
+    net::SocketAddr,
+    path::Path,
+    sync::{Arc, Mutex},
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use alloy_primitives::B256;
+use alloy_sol_types::SolType;
+use anyhow::{anyhow, Context, Result};
+use common::{
+    contract::{ContractError, IBonsaiPayEvents},
+    jwks::{Jwks, JwksClient},
+    ClaimKey, ClaimNonce, JwkKeys, ProofInputs, ProofOutputs,
+};
+use ethers::types::Address;
+use log::{error, info, warn};
+use oidc_validator::{verify_claim, OidcErr};
+use serde_json::json;
+use sp1_sdk::{HashableKey, SP1ProofWithPublicValues, SP1Stdin};
+use thiserror::Error;
+use tokio::sync::mpsc;
+use warp::{http::StatusCode, Filter, Reply};
+
+use crate::{
+    claim::{claim_calldata, contract_events},
+    config::Config,
+    context::ProverContext,
+    execute,
+    jobs::{Job, JobState, JobStore},
+    preflight::preflight,
+    relayer::{RelayError, Relayer, RelayerArgs},
+    tx::{TxError, TxSender, MAX_SEND_ATTEMPTS},
+    ExecuteError,
+};
+
+const HEADER_XAUTH: &str = "X-Auth-Token";
+/// JSON preimage of the token's nonce, see `ClaimNonce`.
+const HEADER_XNONCE: &str = "X-Claim-Nonce";
+/// What the claim ID is derived from, see `ClaimKey`. Defaults to the identifier.
+const HEADER_XCLAIM_KEY: &str = "X-Claim-Key";
+/// Salt of a salted claim, see `ClaimScheme`.
+const HEADER_XSALT: &str = "X-Claim-Salt";
+
+/// A claim the service refuses to prove.
+#[derive(Debug, Error)]
+pub enum ClaimRejection {
+    #[error("malformed nonce preimage: {0}")]
+    Nonce(serde_json::Error),
+    #[error("unknown claim key {0}")]
+    ClaimKey(String),
+    #[error("malformed salt {0}")]
+    Salt(String),
+    #[error("nonce is for chain {0}, not this service's")]
+    Chain(u64),
+    #[error("nonce is for contract {0}, not this service's")]
+    Contract(alloy_primitives::Address),
+    #[error("claim rejected: {0}")]
+    Token(#[from] OidcErr),
+    #[error("relayer refused the claim: {0}")]
+    Relay(#[from] RelayError),
+    #[error("claim would revert: {0}")]
+    Reverted(#[from] ContractError),
+}
+
+/// State shared by all requests and the worker.
+pub struct ProverService {
+    context: ProverContext,
+    config: Config,
+    jwks: JwksClient,
+    sender: TxSender,
+    relayer: Relayer,
+    clock: fn() -> u64,
+    jobs: JobStore,
+    queue: mpsc::UnboundedSender<String>,
+    queued: Mutex<Option<mpsc::UnboundedReceiver<String>>>,
+}
+
+impl ProverService {
+    pub fn new(context: ProverContext, config: Config, jobs: JobStore) -> Result<Self> {
+        let (queue, queued) = mpsc::unbounded_channel();
+        let sender = TxSender::from_config(&config)?;
+        Ok(Self {
+            context,
+            jwks: config.jwks_client(),
+            relayer: Relayer::new(sender.address().0.into(), &RelayerArgs::default()),
+            sender,
+            config,
+            clock: unix_time,
+            jobs,
+            queue,
+            queued: Mutex::new(Some(queued)),
+        })
+    }
+
+    /// Validates tokens at the time `clock` returns, in seconds since the UNIX epoch, instead
+    /// of the system time.
+    pub fn with_clock(mut self, clock: fn() -> u64) -> Self {
+        self.clock = clock;
+        self
+    }
+
+    /// Relays claims under `args` instead of sponsoring all of them.
+    pub fn with_relayer(mut self, args: &RelayerArgs) -> Self {
+        self.relayer = Relayer::new(self.relayer.address(), args);
+        self
+    }
+
+    pub fn context(&self) -> &ProverContext {
+        &self.context
+    }
+
+    pub fn jobs(&self) -> &JobStore {
+        &self.jobs
+    }
+
+    /// Starts the worker, queueing the incomplete jobs of previous runs first.
+    pub fn start(self: &Arc<Self>) -> Result<()> {
+        let mut queued = self
+            .queued
+            .lock()
+            .unwrap()
+            .take()
+            .ok_or_else(|| anyhow!("worker already started"))?;
+        for job in self.jobs.incomplete()? {
+            info!("Resuming job {} ({})", job.id, job.state.as_str());
+            self.queue.send(job.id)?;
+        }
+
+        let service = self.clone();
+        tokio::spawn(async move {
+            while let Some(id) = queued.recv().await {
+                service.run(&id).await;
+            }
+        });
+        Ok(())
+    }
+
+    /// Queues the claim of `token` for the deposit made with `claim_key` and `salt`.
+    pub async fn submit(
+        &self,
+        token: String,
+        nonce: ClaimNonce,
+        claim_key: ClaimKey,
+        salt: Option<B256>,
+    ) -> Result<Job> {
+        if nonce.chain_id != self.config.chain_id()? {
+            return Err(ClaimRejection::Chain(nonce.chain_id).into());
+        }
+        if nonce.contract != self.config.contract()? {
+            return Err(ClaimRejection::Contract(nonce.contract).into());
+        }
+        let now = (self.clock)();
+        self.relayer
+            .admit(&nonce, now)
+            .map_err(ClaimRejection::from)?;
+
+        // Validating the token natively yields the claim ID the program commits, so that claims
+        // over its rate limit are refused before they are queued.
+        let jwks = self.jwks.fetch().await?;
+        let inputs = self.proof_inputs(&jwks, token, nonce, claim_key, salt)?;
+        let public_values = verify_claim(&inputs).map_err(ClaimRejection::from)?;
+        let outputs =
+            ProofOutputs::abi_decode(&public_values, true).context("decoding the public values")?;
+        self.relayer
+            .admit_claim_id(outputs.claimId, now)
+            .map_err(ClaimRejection::from)?;
+
+        let ProofInputs {
+            jwt,
+            nonce,
+            claim_key,
+            salt,
+            ..
+        } = inputs;
+        let job = self.jobs.create(jwt, nonce, claim_key, salt)?;
+        self.queue.send(job.id.clone())?;
+        Ok(job)
+    }
+
+    async fn run(self: &Arc<Self>, id: &str) {
+        if let Err(err) = self.process(id).await {
+            if err.downcast_ref::<ClaimRejection>().is_none() {
+                error!("Job {id}: {err:#}");
+            }
+            if let Err(err) = self.jobs.fail(id, &format!("{err:#}")) {
+                error!("Job {id}: {err:#}");
+            }
+        }
+    }
+
+    /// Proves the job's claim and sends it to the contract, skipping the steps a previous run
+    /// completed.
+    async fn process(self: &Arc<Self>, id: &str) -> Result<()> {
+        let mut job = self.jobs.get(id)?.ok_or_else(|| anyhow!("no job {id}"))?;
+        let mut sent = job.transaction_hash;
+        let calldata = match job.calldata.take() {
+            Some(calldata) => calldata,
+            None => {
+                let calldata = self.prove_job(job).await?;
+                self.jobs.set_calldata(id, &calldata)?;
+                calldata
+            }
+        };
+
+        let contract = Address::from(self.config.contract()?.into_array());
+        for attempt in 1..=MAX_SEND_ATTEMPTS {
+            let hash = match sent {
+                Some(hash) => hash,
+                None => {
+                    self.simulate(contract, &calldata).await?;
+                    let hash = self.sender.send(contract, calldata.clone().into()).await?;
+                    info!("Job {id}: claim transaction {hash:?}");
+                    self.jobs.set_transaction_hash(id, hash)?;
+                    hash
+                }
+            };
+            match self.sender.wait(hash).await {
+                Ok(receipt) => {
+                    for event in contract_events(&receipt, contract) {
+                        if let IBonsaiPayEvents::Claimed(claimed) = event {
+                            info!(
+                                "Job {id}: claimed {} wei for {}",
+                                claimed.amount, claimed.recipient
+                            );
+                        }
+                    }
+                    return self.jobs.finish(id);
+                }
+                Err(TxError::Dropped(hash)) => {
+                    warn!("Job {id}: claim transaction {hash:?} dropped (attempt {attempt})");
+                    sent = None;
+                }
+                Err(err) => return Err(err.into()),
+            }
+        }
+
+        Err(anyhow!(
+            "claim transaction dropped {MAX_SEND_ATTEMPTS} times"
+        ))
+    }
+
+    async fn prove_job(self: &Arc<Self>, job: Job) -> Result<Vec<u8>> {
+        let id = job.id.as_str();
+        self.jobs.set_state(id, JobState::Executing)?;
+        let jwks = self.jwks.fetch().await?;
+        let inputs =
+            Arc::new(self.proof_inputs(&jwks, job.token, job.nonce, job.claim_key, job.salt)?);
+        self.preflight(&inputs, &jwks.keys).await?;
+
+        // Executing and proving take seconds to minutes of CPU, keep them off the async workers.
+        let (service, execute_inputs) = (self.clone(), inputs.clone());
+        tokio::task::spawn_blocking(move || service.execute(&execute_inputs))
+            .await
+            .context("execution task panicked")??;
+
+        self.jobs.set_state(id, JobState::Proving)?;
+        let service = self.clone();
+        let proof = tokio::task::spawn_blocking(move || service.prove(&inputs))
+            .await
+            .context("proving task panicked")??;
+
+        Ok(claim_calldata(&proof).to_vec())
+    }
+
+    /// The program's inputs for the claim of `token`, validated at the current time.
+    fn proof_inputs(
+        &self,
+        jwks: &Jwks,
+        token: String,
+        nonce: ClaimNonce,
+        claim_key: ClaimKey,
+        salt: Option<B256>,
+    ) -> Result<ProofInputs> {
+        Ok(ProofInputs {
+            identity_provider: self.config.identity_provider(),
+            jwt: token,
+            cert: jwks.raw.clone(),
+            timestamp: (self.clock)(),
+            policy: self.config.policy()?,
+            claim_key,
+            salt,
+            nonce,
+        })
+    }
+
+    /// Checks that the contract would accept a proof of `inputs`, see `preflight`.
+    async fn preflight(self: &Arc<Self>, inputs: &ProofInputs, jwks: &JwkKeys) -> Result<()> {
+        let service = self.clone();
+        let vkey = tokio::task::spawn_blocking(move || service.context.vk().bytes32())
+            .await
+            .context("setup task panicked")?;
+        preflight(
+            self.config.rpc_url()?,
+            self.config.contract()?,
+            inputs,
+            jwks,
+            &vkey,
+        )
+        .await
+    }
+
+    fn execute(&self, inputs: &ProofInputs) -> Result<ProofOutputs> {
+        match execute(self.context.client(), inputs) {
+            Ok((public_values, _)) => ProofOutputs::abi_decode(public_values.as_slice(), true)
+                .context("decoding the public values"),
+            Err(ExecuteError::Rejected(rejection)) => Err(ClaimRejection::from(rejection).into()),
+            Err(err) => Err(err.into()),
+        }
+    }
+
+    /// Checks that the claim would succeed, so that a reverting claim is not paid for.
+    async fn simulate(&self, contract: Address, calldata: &[u8]) -> Result<()> {
+        match self
+            .sender
+            .simulate(contract, calldata.to_vec().into())
+            .await
+        {
+            Ok(()) => Ok(()),
+            Err(TxError::CallReverted(data)) => {
+                Err(ClaimRejection::from(ContractError::decode(&data)).into())
+            }
+            Err(err) => Err(err.into()),
+        }
+    }
+
+    fn prove(&self, inputs: &ProofInputs) -> Result<SP1ProofWithPublicValues> {
+        let mut stdin = SP1Stdin::new();
+        stdin.write(inputs);
+        self.context
+            .client()
+            .prove(self.context.pk(), stdin)
+            .plonk()
+            .run()
+    }
+}
+
+fn unix_time() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .expect("system time before UNIX epoch")
+        .as_secs()
+}
+
+/// The service's routes, `GET /auth`, `GET /jobs/{id}` and `GET /relayer`.
+pub fn routes(
+    service: Arc<ProverService>,
+) -> impl Filter<Extract = (impl Reply,), Error = warp::Rejection> + Clone {
+    let cors = warp::cors()
+        .allow_any_origin()
+        .allow_methods(vec!["GET"])
+        .allow_headers(vec![
+            "content-type",
+            "x-auth-token",
+            "x-claim-nonce",
+            "x-claim-key",
+            "x-claim-salt",
+        ])
+        .max_age(3600);
+    let service = warp::any().map(move || service.clone());
+
+    let auth = warp::path("auth")
+        .and(warp::get())
+        .and(warp::path::end())
+        .and(service.clone())
+        .and(warp::header::<String>(HEADER_XAUTH))
+        .and(warp::header::<String>(HEADER_XNONCE))
+        .and(warp::header::optional::<String>(HEADER_XCLAIM_KEY))
+        .and(warp::header::optional::<String>(HEADER_XSALT))
+        .and_then(handle_jwt_authentication);
+    let job = warp::path!("jobs" / String)
+        .and(warp::get())
+        .and(service.clone())
+        .and_then(handle_job_status);
+    let relayer =
+        warp::path!("relayer")
+            .and(warp::get())
+            .and(service)
+            .map(|service: Arc<ProverService>| {
+                warp::reply::json(&json!({
+                    "address": service.relayer.address(),
+                    "fee": service.relayer.fee(),
+                }))
+            });
+
+    auth.or(job).or(relayer).with(cors)
+}
+
+/// Sets up the program and serves the routes on `addr` until the process is stopped. Jobs are
+/// persisted in the SQLite database at `jobs`.
+pub async fn serve(
+    config: Config,
+    relayer: &RelayerArgs,
+    addr: SocketAddr,
+    jobs: &Path,
+) -> Result<()> {
+    // Fail on startup rather than on the first claim.
+    config.rpc_url()?;
+    config.chain_id()?;
+    config.contract()?;
+    config.eth_wallet_private_key()?;
+    config.policy()?;
+
+    let context = ProverContext::new(config.key_cache.clone());
+    let service = ProverService::new(context, config, JobStore::open(jobs)?)?.with_relayer(relayer);
+    let service = Arc::new(service);
+
+    // Set the keys up before taking claims, rather than while proving the first one.
+    let warm = service.clone();
+    tokio::task::spawn_blocking(move || {
+        warm.context().pk();
+    })
+    .await?;
+    service.start()?;
+
+    info!("Listening on {addr}");
+    warp::serve(routes(service)).run(addr).await;
+
+    Ok(())
+}
+
+async fn handle_jwt_authentication(
+    service: Arc<ProverService>,
+    token: String,
+    nonce: String,
+    claim_key: Option<String>,
+    salt: Option<String>,
+) -> Result<warp::reply::Response, Infallible> {
+    let result = match parse_claim(&nonce, claim_key, salt) {
+        Ok((nonce, claim_key, salt)) => service.submit(token, nonce, claim_key, salt).await,
+        Err(rejection) => Err(rejection.into()),
+    };
+
+    Ok(match result {
+        Ok(job) => {
+            let reply = warp::reply::json(&json!({ "jobId": job.id }));
+            warp::reply::with_status(reply, StatusCode::ACCEPTED).into_response()
+        }
+        Err(err) => {
+            let status = if err.downcast_ref::<ClaimRejection>().is_some() {
+                StatusCode::BAD_REQUEST
+            } else {
+                error!("{err:#}");
+                StatusCode::INTERNAL_SERVER_ERROR
+            };
+            error_reply(&format!("{err:#}"), status)
+        }
+    })
+}
+
+/// Parses the nonce preimage, claim key and salt headers of `/auth`.
+fn parse_claim(
+    nonce: &str,
+    claim_key: Option<String>,
+    salt: Option<String>,
+) -> Result<(ClaimNonce, ClaimKey, Option<B256>), ClaimRejection> {
+    let nonce = serde_json::from_str(nonce).map_err(ClaimRejection::Nonce)?;
+    let claim_key = match claim_key {
+        Some(key) => {
+            serde_json::from_value(json!(key)).map_err(|_| ClaimRejection::ClaimKey(key))?
+        }
+        None => ClaimKey::default(),
+    };
+    let salt = salt
+        .map(|salt| salt.parse().map_err(|_| ClaimRejection::Salt(salt)))
+        .transpose()?;
+    Ok((nonce, claim_key, salt))
+}
+
+async fn handle_job_status(
+    id: String,
+    service: Arc<ProverService>,
+) -> Result<warp::reply::Response, Infallible> {
+    Ok(match service.jobs().get(&id) {
+        Ok(Some(job)) => warp::reply::json(&job).into_response(),
+        Ok(None) => error_reply(&format!("no job {id}"), StatusCode::NOT_FOUND),
+        Err(err) => {
+            error!("{err:#}");
+            error_reply(&format!("{err:#}"), StatusCode::INTERNAL_SERVER_ERROR)
+        }
+    })
+}
+
+fn error_reply(error: &str, status: StatusCode) -> warp::reply::Response {
+    let reply = warp::reply::json(&json!({ "error": error }));
+    warp::reply::with_status(reply, status).into_response()
+}
@@ -0,0 +1,358 @@
+//! Sends transactions from the relayer's wallet.
+//!
+//! `TxSender` estimates the gas and fees of each transaction, EIP-1559 fees where the chain
+//! supports them, and assigns nonces itself so that concurrent jobs sending from the same wallet
+//! do not collide. Sends rejected as underpriced are retried with bumped fees, and the nonce is
+//! read again from the node when it is stale.
+
+use std::time::Duration;
+
+use anyhow::{anyhow, Result};
+use ethers::{
+    middleware::Middleware,
+    providers::{Http, PendingTransaction, Provider, ProviderError, RpcError},
+    signers::{LocalWallet, Signer},
+    types::{
+        transaction::eip2718::TypedTransaction, Address, BlockNumber, Bytes,
+        Eip1559TransactionRequest, TransactionReceipt, TransactionRequest, H256, U256,
+    },
+    utils::keccak256,
+};
+use log::{debug, info, warn};
+use thiserror::Error;
+use tokio::sync::Mutex;
+
+use crate::config::Config;
+
+/// How many times a transaction is sent before giving up, counting retries with bumped fees
+/// and sends of dropped transactions.
+pub const MAX_SEND_ATTEMPTS: u32 = 5;
+/// Added to the estimated gas, in percent.
+const GAS_MARGIN_PERCENT: u64 = 20;
+/// Fee increase of a retry, in percent. Nodes require at least 10% to replace a transaction.
+const FEE_BUMP_PERCENT: u64 = 15;
+const DEFAULT_POLL_INTERVAL: Duration = Duration::from_secs(2);
+
+/// JSON-RPC error code of transactions nethermind refuses, with the name of the reason first in
+/// the message.
+const NETHERMIND_TRANSACTION_REJECTED: i64 = -32010;
+
+#[derive(Debug, Error)]
+pub enum TxError {
+    #[error("transaction {0:?} was dropped")]
+    Dropped(H256),
+    #[error("transaction {0:?} reverted")]
+    Reverted(H256),
+    #[error("call reverted with {0}")]
+    CallReverted(Bytes),
+    #[error(transparent)]
+    Provider(#[from] ProviderError),
+}
+
+/// Why the node refused a transaction, as far as `send` can do something about it.
+#[derive(Debug, PartialEq, Eq)]
+enum Refusal {
+    /// The fees are too low to be accepted or to replace a pending transaction.
+    Underpriced,
+    /// The nonce was used by another transaction.
+    NonceTooLow,
+    /// The node already has this exact transaction.
+    AlreadyKnown,
+    Other,
+}
+
+impl Refusal {
+    /// Classifies `err` by its JSON-RPC error code where the node's is specific enough, by its
+    /// message otherwise.
+    fn of(err: &ProviderError) -> Self {
+        let Some(response) = err.as_error_response() else {
+            return Self::of_message(&err.to_string());
+        };
+        match response.code {
+            NETHERMIND_TRANSACTION_REJECTED => {
+                match response.message.split(',').next().unwrap_or_default() {
+                    "FeeTooLow" | "FeeTooLowToCompete" => Self::Underpriced,
+                    "OldNonce" => Self::NonceTooLow,
+                    "AlreadyKnown" => Self::AlreadyKnown,
+                    _ => Self::Other,
+                }
+            }
+            // Geth and erigon refuse every transaction with -32000 and anvil with -32003, only
+            // the message tells the reasons apart.
+            _ => Self::of_message(&response.message),
+        }
+    }
+
+    fn of_message(message: &str) -> Self {
+        let message = message.to_lowercase();
+        if message.contains("underpriced")
+            || message.contains("less than block base fee")
+            || message.contains("fee too low")
+        {
+            Self::Underpriced
+        } else if message.contains("nonce too low") {
+            Self::NonceTooLow
+        } else if message.contains("already known") || message.contains("already imported") {
+            Self::AlreadyKnown
+        } else {
+            Self::Other
+        }
+    }
+}
+
+pub struct TxSender {
+    provider: Provider<Http>,
+    wallet: LocalWallet,
+    /// Nonce of the next transaction, or `None` to read it from the node.
+    nonce: Mutex<Option<U256>>,
+}
+
+impl TxSender {
+    pub fn new(rpc_url: &str, private_key: &str, chain_id: u64) -> Result<Self> {
+        let provider = Provider::<Http>::try_from(rpc_url)?.interval(DEFAULT_POLL_INTERVAL);
+        let wallet = private_key.parse::<LocalWallet>()?.with_chain_id(chain_id);
+        Ok(Self {
+            provider,
+            wallet,
+            nonce: Mutex::new(None),
+        })
+    }
+
+    /// A sender for `config.rpc_url`, signing with `config.eth_wallet_private_key`.
+    pub fn from_config(config: &Config) -> Result<Self> {
+        Self::new(
+            config.rpc_url()?,
+            config.eth_wallet_private_key()?,
+            config.chain_id()?,
+        )
+    }
+
+    /// Polls for receipts every `interval` instead of every two seconds.
+    pub fn with_poll_interval(mut self, interval: Duration) -> Self {
+        self.provider = self.provider.interval(interval);
+        self
+    }
+
+    pub fn address(&self) -> Address {
+        self.wallet.address()
+    }
+
+    pub fn provider(&self) -> &Provider<Http> {
+        &self.provider
+    }
+
+    /// Sends a transaction calling `to` with `data`, returning its hash without waiting for it
+    /// to be mined.
+    pub async fn send(&self, to: Address, data: Bytes) -> Result<H256> {
+        self.send_value(to, data, U256::zero()).await
+    }
+
+    /// Sends a transaction calling `to` with `data` and `value` wei, see `send`.
+    pub async fn send_value(&self, to: Address, data: Bytes, value: U256) -> Result<H256> {
+        let mut tx = self.build(to, data, value).await?;
+
+        // Hold the nonce until the transaction is sent, so concurrent sends take turns.
+        let mut next_nonce = self.nonce.lock().await;
+        for attempt in 1..=MAX_SEND_ATTEMPTS {
+            let nonce = match *next_nonce {
+                Some(nonce) => nonce,
+                None => {
+                    self.provider
+                        .get_transaction_count(self.address(), Some(BlockNumber::Pending.into()))
+                        .await?
+                }
+            };
+            tx.set_nonce(nonce);
+            let signature = self.wallet.sign_transaction(&tx).await?;
+            let raw = tx.rlp_signed(&signature);
+            let hash = H256(keccak256(&raw));
+
+            let err = match self.provider.send_raw_transaction(raw).await {
+                Ok(_) => {
+                    debug!("Sent transaction {hash:?} with nonce {nonce}");
+                    *next_nonce = Some(nonce + 1);
+                    return Ok(hash);
+                }
+                Err(err) => err,
+            };
+            match Refusal::of(&err) {
+                Refusal::AlreadyKnown => {
+                    *next_nonce = Some(nonce + 1);
+                    return Ok(hash);
+                }
+                Refusal::Underpriced => {
+                    warn!("Transaction underpriced ({err}), bumping its fees (attempt {attempt})");
+                    bump_fees(&mut tx);
+                }
+                Refusal::NonceTooLow => {
+                    warn!("Nonce {nonce} already used, reading it again (attempt {attempt})");
+                    *next_nonce = None;
+                }
+                Refusal::Other => {
+                    // The node may not have seen a nonce it was sent, read it again next time.
+                    *next_nonce = None;
+                    return Err(err.into());
+                }
+            }
+        }
+
+        Err(anyhow!(
+            "transaction to {to:?} not accepted after {MAX_SEND_ATTEMPTS} attempts"
+        ))
+    }
+
+    /// Calls `to` with `data` from the sender's address without sending a transaction, to learn
+    /// whether it would revert.
+    pub async fn simulate(&self, to: Address, data: Bytes) -> Result<(), TxError> {
+        let tx: TypedTransaction = TransactionRequest::new()
+            .from(self.address())
+            .to(to)
+            .data(data)
+            .into();
+        match self.provider.call(&tx, None).await {
+            Ok(_) => Ok(()),
+            Err(err) => match err.as_error_response().and_then(|err| err.as_revert_data()) {
+                Some(data) => Err(TxError::CallReverted(data)),
+                None => Err(err.into()),
+            },
+        }
+    }
+
+    /// Waits for the transaction `hash` to be mined, and checks that it succeeded.
+    pub async fn wait(&self, hash: H256) -> Result<TransactionReceipt, TxError> {
+        let Some(receipt) = PendingTransaction::new(hash, &self.provider).await? else {
+            // Its nonce is free again, and later nonces handed out may be stuck behind it.
+            *self.nonce.lock().await = None;
+            return Err(TxError::Dropped(hash));
+        };
+        match receipt.status.map(|status| status.as_u64()) {
+            Some(1) => {
+                info!(
+                    "Transaction {hash:?} mined in block {:?}",
+                    receipt.block_number
+                );
+                Ok(receipt)
+            }
+            _ => Err(TxError::Reverted(hash)),
+        }
+    }
+
+    /// The transaction with its gas and fees estimated, but without a nonce.
+    async fn build(&self, to: Address, data: Bytes, value: U256) -> Result<TypedTransaction> {
+        let mut tx: TypedTransaction = match self.provider.estimate_eip1559_fees(None).await {
+            Ok((max_fee, max_priority_fee)) => Eip1559TransactionRequest::new()
+                .max_fee_per_gas(max_fee)
+                .max_priority_fee_per_gas(max_priority_fee)
+                .into(),
+            Err(err) => {
+                debug!("No EIP-1559 fees ({err}), using a legacy gas price");
+                TransactionRequest::new()
+                    .gas_price(self.provider.get_gas_price().await?)
+                    .into()
+            }
+        };
+        tx.set_from(self.address())
+            .set_to(to)
+            .set_data(data)
+            .set_value(value)
+            .set_chain_id(self.wallet.chain_id());
+
+        let gas = self.provider.estimate_gas(&tx, None).await?;
+        tx.set_gas(gas * (100 + GAS_MARGIN_PERCENT) / 100);
+        Ok(tx)
+    }
+}
+
+fn bump_fees(tx: &mut TypedTransaction) {
+    let bump = |fee: &mut Option<U256>| {
+        if let Some(fee) = fee {
+            *fee = *fee * (100 + FEE_BUMP_PERCENT) / 100;
+        }
+    };
+    match tx {
+        TypedTransaction::Legacy(tx) => bump(&mut tx.gas_price),
+        TypedTransaction::Eip2930(tx) => bump(&mut tx.tx.gas_price),
+        TypedTransaction::Eip1559(tx) => {
+            bump(&mut tx.max_fee_per_gas);
+            bump(&mut tx.max_priority_fee_per_gas);
+        }
+    }
+}
+
+#[cfg(test)]
+mod test_tx {
+    use ethers::providers::{HttpClientError, JsonRpcError, ProviderError};
+
+    use super::Refusal;
+
+    fn refusal(code: i64, message: &str) -> Refusal {
+        Refusal::of(&ProviderError::JsonRpcClientError(Box::new(
+            HttpClientError::JsonRpcError(JsonRpcError {
+                code,
+                message: message.to_string(),
+                data: None,
+            }),
+        )))
+    }
+
+    #[test]
+    fn test_geth_refusals() {
+        let underpriced = refusal(-32000, "replacement transaction underpriced");
+        assert_eq!(underpriced, Refusal::Underpriced);
+        let below_base_fee = refusal(
+            -32000,
+            "max fee per gas less than block base fee: address 0x01, maxFeePerGas: 1, baseFee: 7",
+        );
+        assert_eq!(below_base_fee, Refusal::Underpriced);
+        let nonce = refusal(-32000, "nonce too low: address 0x01, tx: 0 state: 1");
+        assert_eq!(nonce, Refusal::NonceTooLow);
+        assert_eq!(refusal(-32000, "already known"), Refusal::AlreadyKnown);
+        let funds = refusal(-32000, "insufficient funds for gas * price + value");
+        assert_eq!(funds, Refusal::Other);
+    }
+
+    #[test]
+    fn test_erigon_refusals() {
+        assert_eq!(
+            refusal(-32000, "FEE_TOO_LOW: fee too low"),
+            Refusal::Underpriced
+        );
+        assert_eq!(
+            refusal(-32000, "INVALID: nonce too low"),
+            Refusal::NonceTooLow
+        );
+        assert_eq!(
+            refusal(-32000, "ALREADY_EXISTS: already known"),
+            Refusal::AlreadyKnown
+        );
+    }
+
+    #[test]
+    fn test_nethermind_refusals() {
+        let underpriced = refusal(-32010, "FeeTooLow, MaxFeePerGas too low. BaseFee: 7");
+        assert_eq!(underpriced, Refusal::Underpriced);
+        assert_eq!(refusal(-32010, "FeeTooLowToCompete"), Refusal::Underpriced);
+        let nonce = refusal(
+            -32010,
+            "OldNonce, Current nonce: 1, nonce of rejected tx: 0",
+        );
+        assert_eq!(nonce, Refusal::NonceTooLow);
+        assert_eq!(refusal(-32010, "AlreadyKnown"), Refusal::AlreadyKnown);
+        assert_eq!(refusal(-32010, "InsufficientFunds"), Refusal::Other);
+    }
+
+    #[test]
+    fn test_anvil_refusals() {
+        let underpriced = refusal(-32003, "replacement transaction underpriced");
+        assert_eq!(underpriced, Refusal::Underpriced);
+        assert_eq!(refusal(-32003, "nonce too low"), Refusal::NonceTooLow);
+        let imported = refusal(-32003, "transaction already imported");
+        assert_eq!(imported, Refusal::AlreadyKnown);
+    }
+
+    #[test]
+    fn test_refusal_without_json_rpc_error() {
+        let err = ProviderError::CustomError("nonce too low".to_string());
+        assert_eq!(Refusal::of(&err), Refusal::NonceTooLow);
+    }
+}
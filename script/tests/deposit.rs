@@ -0,0 +1,112 @@
+//! Deposits to a stand-in Ethereum node.
+
+mod node;
+
+use alloy_primitives::{Address, U256};
+use common::{ClaimKey, GOOGLE_ISSUERS};
+use node::{MockNode, CHAIN_ID, CONTRACT, WALLET_KEY};
+use sp1_pay_script::{
+    deposit::{deposit, parse_deposits, DepositRecipient},
+    tx::TxSender,
+};
+
+fn contract() -> Address {
+    CONTRACT.parse().unwrap()
+}
+
+fn recipient(email: &str) -> DepositRecipient {
+    DepositRecipient {
+        claim_key: ClaimKey::Identifier,
+        identity: email.to_string(),
+        salt: None,
+    }
+}
+
+#[tokio::test]
+async fn test_deposit_is_sent_to_claim_id() {
+    let node = MockNode::default();
+    let sender = TxSender::new(&node.start(), WALLET_KEY, CHAIN_ID).unwrap();
+    let recipient = recipient("Test@Example.com");
+    let (_, claim_id) = recipient.claim_id(GOOGLE_ISSUERS[0]);
+
+    let first = deposit(
+        &sender,
+        contract(),
+        GOOGLE_ISSUERS[0],
+        &recipient,
+        U256::from(1_000),
+    )
+    .await
+    .unwrap();
+    assert_eq!(first.claim_id, claim_id);
+    assert_eq!(first.previous_balance, U256::ZERO);
+
+    let second = deposit(
+        &sender,
+        contract(),
+        GOOGLE_ISSUERS[0],
+        &recipient,
+        U256::from(500),
+    )
+    .await
+    .unwrap();
+    assert_eq!(second.previous_balance, U256::from(1_000));
+
+    let mined = node.mined();
+    assert_eq!(mined.len(), 2);
+    assert_eq!(mined[0].hash, first.transaction_hash);
+    assert_eq!(mined[0].value, ethers::types::U256::from(1_000));
+    assert_eq!(&mined[0].input[4..], claim_id.as_slice());
+}
+
+#[tokio::test]
+async fn test_zero_deposit_is_not_sent() {
+    let node = MockNode::default();
+    let sender = TxSender::new(&node.start(), WALLET_KEY, CHAIN_ID).unwrap();
+
+    let err = deposit(
+        &sender,
+        contract(),
+        GOOGLE_ISSUERS[0],
+        &recipient("test@example.com"),
+        U256::ZERO,
+    )
+    .await
+    .unwrap_err();
+    assert!(err.to_string().contains("must not be zero"), "{err:#}");
+    assert!(node.state().received.is_empty());
+}
+
+#[tokio::test]
+async fn test_bulk_deposits() {
+    let node = MockNode::default();
+    let sender = TxSender::new(&node.start(), WALLET_KEY, CHAIN_ID).unwrap();
+    let rows = parse_deposits(
+        "recipient,amount,key\n\
+         alice@example.com,100,\n\
+         1234567890,200,subject\n",
+    )
+    .unwrap();
+
+    for row in &rows {
+        deposit(
+            &sender,
+            contract(),
+            GOOGLE_ISSUERS[0],
+            &row.recipient,
+            row.amount,
+        )
+        .await
+        .unwrap();
+    }
+
+    let state = node.state();
+    for row in &rows {
+        let (_, claim_id) = row.recipient.claim_id(GOOGLE_ISSUERS[0]);
+        assert_eq!(
+            state.balances[&claim_id],
+            ethers::types::U256::from(row.amount.to::<u64>())
+        );
+    }
+    assert_eq!(state.mined.len(), 2);
+}
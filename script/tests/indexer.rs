@@ -0,0 +1,159 @@
+//! Indexing the events of a stand-in Ethereum node.
+
+mod node;
+
+use std::sync::Arc;
+
+use alloy_primitives::{Address, B256, U256};
+use common::contract::IBonsaiPay;
+use ethers::types::Log;
+use node::{contract_log, MockNode, CONTRACT};
+use serde_json::Value;
+use sp1_pay_script::{
+    events::{EventKind, EventStore},
+    indexer::{routes, Indexer},
+};
+use warp::http::StatusCode;
+
+const RECIPIENT: Address = Address::repeat_byte(0x11);
+const RELAYER: Address = Address::repeat_byte(0x22);
+
+fn indexer(node: &MockNode) -> Indexer {
+    let store = Arc::new(EventStore::in_memory().unwrap());
+    Indexer::new(&node.start(), CONTRACT.parse().unwrap(), store).unwrap()
+}
+
+fn deposited(claim_id: B256, amount: u64) -> Log {
+    contract_log(&IBonsaiPay::Deposited {
+        claimId: claim_id,
+        amount: U256::from(amount),
+    })
+}
+
+/// The logs of a claim of `amount` relayed for `fee`.
+fn claimed(claim_id: B256, amount: u64, fee: u64) -> Vec<Log> {
+    vec![
+        contract_log(&IBonsaiPay::RelayerPaid {
+            relayer: RELAYER,
+            claimId: claim_id,
+            fee: U256::from(fee),
+        }),
+        contract_log(&IBonsaiPay::Claimed {
+            recipient: RECIPIENT,
+            claimId: claim_id,
+            amount: U256::from(amount),
+        }),
+    ]
+}
+
+async fn get(indexer: &Indexer, path: &str) -> (StatusCode, Value) {
+    let response = warp::test::request()
+        .method("GET")
+        .path(path)
+        .reply(&routes(indexer.store().clone()))
+        .await;
+    let body = serde_json::from_slice(response.body()).unwrap();
+    (response.status(), body)
+}
+
+#[tokio::test]
+async fn test_deposits_and_claims_are_indexed() {
+    let node = MockNode::default();
+    let indexer = indexer(&node);
+    let (alice, bob) = (B256::repeat_byte(0xa), B256::repeat_byte(0xb));
+    node.mine_block(vec![]);
+    node.mine_block(vec![deposited(alice, 100), deposited(bob, 7)]);
+    node.mine_block(vec![deposited(alice, 50)]);
+    node.mine_block(claimed(alice, 140, 10));
+    // Events of other contracts with the same signatures.
+    let mut foreign = deposited(alice, 1_000);
+    foreign.address = ethers::types::Address::repeat_byte(0xee);
+    node.mine_block(vec![deposited(alice, 30), foreign]);
+
+    assert_eq!(indexer.poll().await.unwrap(), Some(4));
+
+    let history = indexer.store().history(alice).unwrap();
+    let kinds: Vec<EventKind> = history.events.iter().map(|event| event.kind).collect();
+    assert_eq!(
+        kinds,
+        [
+            EventKind::Deposited,
+            EventKind::Deposited,
+            EventKind::RelayerPaid,
+            EventKind::Claimed,
+            EventKind::Deposited,
+        ]
+    );
+    assert_eq!(history.events[3].account, Some(RECIPIENT));
+    assert_eq!(history.balances.deposited, U256::from(180));
+    assert_eq!(history.balances.claimed, U256::from(140));
+    assert_eq!(history.balances.fees, U256::from(10));
+    assert_eq!(history.balances.outstanding, U256::from(30));
+
+    let (status, totals) = get(&indexer, "/totals").await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(totals["claimIds"], 2);
+    assert_eq!(totals["blockNumber"], 4);
+    assert_eq!(totals["outstanding"], "0x25");
+
+    let (status, history) = get(&indexer, &format!("/claims/{alice}")).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(history["outstanding"], "0x1e");
+    assert_eq!(history["events"].as_array().unwrap().len(), 5);
+    assert_eq!(history["events"][0]["kind"], "deposited");
+
+    let (status, _) = get(&indexer, "/claims/alice").await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+}
+
+#[tokio::test]
+async fn test_reorged_events_are_dropped() {
+    let node = MockNode::default();
+    let indexer = indexer(&node);
+    let claim_id = B256::repeat_byte(0xa);
+    node.mine_block(vec![deposited(claim_id, 100)]);
+    node.mine_block(vec![deposited(claim_id, 50)]);
+    node.mine_block(claimed(claim_id, 150, 0));
+    indexer.poll().await.unwrap();
+    assert_eq!(
+        indexer.store().history(claim_id).unwrap().balances.claimed,
+        U256::from(150)
+    );
+
+    // The second deposit and the claim are replaced by another deposit.
+    node.reorg(2);
+    node.mine_block(vec![]);
+    node.mine_block(vec![]);
+    node.mine_block(vec![deposited(claim_id, 20)]);
+    assert_eq!(indexer.poll().await.unwrap(), Some(3));
+
+    let history = indexer.store().history(claim_id).unwrap();
+    assert_eq!(history.events.len(), 2);
+    assert_eq!(history.balances.claimed, U256::ZERO);
+    assert_eq!(history.balances.outstanding, U256::from(120));
+}
+
+#[tokio::test]
+async fn test_unconfirmed_blocks_are_not_indexed() {
+    let node = MockNode::default();
+    let indexer = indexer(&node).with_confirmations(2);
+    let claim_id = B256::repeat_byte(0xa);
+    node.mine_block(vec![]);
+    node.mine_block(vec![deposited(claim_id, 100)]);
+    node.mine_block(vec![]);
+
+    assert_eq!(indexer.poll().await.unwrap(), Some(0));
+    assert!(indexer.store().history(claim_id).unwrap().events.is_empty());
+
+    node.mine_block(vec![]);
+    assert_eq!(indexer.poll().await.unwrap(), Some(1));
+    assert_eq!(
+        indexer
+            .store()
+            .history(claim_id)
+            .unwrap()
+            .balances
+            .outstanding,
+        U256::from(100)
+    );
+}
@@ -0,0 +1,312 @@
+//! Stand-in for an Ethereum node, shared by the integration tests.
+
+#![allow(dead_code)]
+
+use std::{
+    collections::{HashMap, VecDeque},
+    sync::{Arc, Mutex, MutexGuard},
+};
+
+use alloy_primitives::B256;
+use alloy_sol_types::SolEvent;
+use ethers::{
+    abi::{encode, Token},
+    types::{
+        Address, Block, Bytes, FeeHistory, Log, Transaction, TransactionReceipt, H256, U256, U64,
+    },
+    utils::{id, keccak256, rlp::Rlp},
+};
+use serde_json::{json, Value};
+use sp1_pay_script::preflight::ContractSettings;
+use warp::Filter;
+
+pub const CHAIN_ID: u64 = 11_155_111;
+pub const CONTRACT: &str = "0x5FbDB2315678afecb367f032d93F642f64180aa3";
+/// Private key of anvil's first development account.
+pub const WALLET_KEY: &str = "0xac0974bec39a17e36ba4a6b4d238ff944bacb478cbed5efcae784d7bf4f2ff80";
+/// Address of `WALLET_KEY`.
+pub const RELAYER: &str = "0xf39Fd6e51aad88F6F4ce6aB8827279cffFb92266";
+pub const BASE_FEE: u64 = 1_000_000_000;
+
+#[derive(Default)]
+pub struct NodeState {
+    /// Transactions mined, in order.
+    pub mined: Vec<Transaction>,
+    /// Every transaction sent, including those refused or dropped.
+    pub received: Vec<Transaction>,
+    /// Errors to refuse the next transactions sent with.
+    pub refusals: VecDeque<String>,
+    /// Number of the next transactions sent to accept but never mine.
+    pub drops: usize,
+    /// Whether the node predates EIP-1559, with no base fee.
+    pub legacy: bool,
+    /// Settings of the contract at `CONTRACT`, read through `eth_call`.
+    pub settings: Option<ContractSettings>,
+    /// Revert data of simulated claims, which succeed if unset.
+    pub claim_revert: Option<Bytes>,
+    /// Balances of the claim IDs deposited to by mined transactions.
+    pub balances: HashMap<B256, U256>,
+    /// Blocks mined with `MockNode::mine_block`, from block 0. Without them the node is always at
+    /// block 1, with no logs.
+    pub chain: Vec<MockBlock>,
+    /// Number of reorgs, so that blocks replacing others get new hashes.
+    pub forks: u64,
+}
+
+pub struct MockBlock {
+    pub hash: H256,
+    /// Logs of the block, without their block and index.
+    pub logs: Vec<Log>,
+}
+
+/// Answers the calls a `TxSender` and the preflight check make. Transactions are mined as soon
+/// as they are sent, and must use the next nonce.
+#[derive(Clone, Default)]
+pub struct MockNode {
+    state: Arc<Mutex<NodeState>>,
+}
+
+impl MockNode {
+    /// Serves the node on a free port, returning its URL.
+    pub fn start(&self) -> String {
+        let node = self.clone();
+        let rpc = warp::post()
+            .and(warp::body::json())
+            .map(move |request: Value| warp::reply::json(&node.handle(&request)));
+        let (addr, server) = warp::serve(rpc).bind_ephemeral(([127, 0, 0, 1], 0));
+        tokio::spawn(server);
+
+        format!("http://{addr}")
+    }
+
+    pub fn state(&self) -> MutexGuard<'_, NodeState> {
+        self.state.lock().unwrap()
+    }
+
+    pub fn deploy(&self, settings: ContractSettings) {
+        self.state().settings = Some(settings);
+    }
+
+    pub fn mined(&self) -> Vec<Transaction> {
+        self.state().mined.clone()
+    }
+
+    /// Mines a block with `logs`, returning its number.
+    pub fn mine_block(&self, logs: Vec<Log>) -> u64 {
+        let mut state = self.state();
+        let number = state.chain.len() as u64;
+        let hash = keccak256(encode(&[
+            Token::Uint(number.into()),
+            Token::Uint(state.forks.into()),
+        ]));
+        state.chain.push(MockBlock {
+            hash: H256(hash),
+            logs,
+        });
+        number
+    }
+
+    /// Drops the last `depth` blocks, to be replaced with other blocks.
+    pub fn reorg(&self, depth: usize) {
+        let mut state = self.state();
+        let height = state.chain.len() - depth;
+        state.chain.truncate(height);
+        state.forks += 1;
+    }
+
+    fn handle(&self, request: &Value) -> Value {
+        let params = &request["params"];
+        let mut state = self.state();
+
+        let result = match request["method"].as_str().unwrap_or_default() {
+            "eth_chainId" => json!(U64::from(CHAIN_ID)),
+            "eth_blockNumber" => match state.chain.len() {
+                0 => json!(U64::from(1)),
+                height => json!(U64::from(height - 1)),
+            },
+            "eth_getBlockByNumber" => json!(block(&state, &params[0])),
+            "eth_getLogs" => json!(logs(&state, &params[0])),
+            "eth_feeHistory" => json!(FeeHistory {
+                base_fee_per_gas: vec![U256::from(BASE_FEE)],
+                gas_used_ratio: vec![0.5],
+                oldest_block: U256::one(),
+                reward: vec![vec![U256::from(BASE_FEE / 10)]],
+            }),
+            "eth_gasPrice" => json!(U256::from(BASE_FEE)),
+            "eth_estimateGas" => json!(U64::from(500_000)),
+            "eth_getTransactionCount" => json!(U64::from(state.mined.len())),
+            "eth_getTransactionByHash" => json!(find(&state.mined, &params[0])),
+            "eth_getTransactionReceipt" => {
+                json!(find(&state.mined, &params[0]).map(|tx| TransactionReceipt {
+                    transaction_hash: tx.hash,
+                    block_hash: tx.block_hash,
+                    block_number: tx.block_number,
+                    from: tx.from,
+                    to: tx.to,
+                    status: Some(U64::from(1)),
+                    ..Default::default()
+                }))
+            }
+            // Other calls are simulated claims.
+            "eth_call" => match (call(&state, &params[0]), &state.claim_revert) {
+                (Some(output), _) => json!(output),
+                (None, Some(data)) => return revert(request, data),
+                (None, None) => json!(Bytes::new()),
+            },
+            "eth_sendRawTransaction" => {
+                let raw: Bytes = serde_json::from_value(params[0].clone()).unwrap();
+                let mut tx: Transaction = Rlp::new(&raw).as_val().unwrap();
+                state.received.push(tx.clone());
+
+                if let Some(refusal) = state.refusals.pop_front() {
+                    return rpc_error(request, -32000, &refusal);
+                }
+                let next_nonce = U256::from(state.mined.len());
+                if tx.nonce < next_nonce {
+                    return rpc_error(request, -32000, "nonce too low");
+                }
+                if state.drops > 0 {
+                    state.drops -= 1;
+                    return json!({ "jsonrpc": "2.0", "id": request["id"], "result": tx.hash });
+                }
+                // A real node would queue it until the missing nonces are sent.
+                if tx.nonce > next_nonce {
+                    return rpc_error(request, -32000, "nonce too high");
+                }
+
+                tx.block_hash = Some(H256::repeat_byte(0x01));
+                tx.block_number = Some(U64::from(1));
+                tx.transaction_index = Some(U64::from(state.mined.len()));
+                if tx.input.starts_with(&id("deposit(bytes32)")) {
+                    let claim_id = B256::from_slice(&tx.input[4..36]);
+                    *state.balances.entry(claim_id).or_default() += tx.value;
+                }
+                state.mined.push(tx.clone());
+                json!(tx.hash)
+            }
+            method => return rpc_error(request, -32601, &format!("{method} is not supported")),
+        };
+
+        json!({ "jsonrpc": "2.0", "id": request["id"], "result": result })
+    }
+}
+
+/// Answers `balanceOf`, and once the contract is deployed the getters of `BonsaiPay` read by the
+/// preflight check.
+fn call(state: &NodeState, call: &Value) -> Option<Bytes> {
+    let data = call.get("input").or_else(|| call.get("data"))?;
+    let data: Bytes = serde_json::from_value(data.clone()).ok()?;
+
+    let word = |value: B256| Token::FixedBytes(value.to_vec());
+    let selector = data.get(..4)?;
+    if selector == id("balanceOf(bytes32)") {
+        let claim_id = B256::from_slice(data.get(4..36)?);
+        let balance = state.balances.get(&claim_id).copied().unwrap_or_default();
+        return Some(encode(&[Token::Uint(balance)]).into());
+    }
+
+    let settings = state.settings.clone()?;
+    let output = if selector == id("bonsaiPayVKey()") {
+        word(settings.vkey)
+    } else if selector == id("registeredKeyDigests()") {
+        Token::Array(settings.key_digests.into_iter().map(word).collect())
+    } else if selector == id("policyHash()") {
+        word(settings.policy_hash)
+    } else if selector == id("identityProvider()") {
+        Token::Uint(U256::from(settings.identity_provider))
+    } else {
+        return None;
+    };
+    Some(encode(&[output]).into())
+}
+
+/// Block `number`, a quantity or a tag such as `latest`.
+fn block(state: &NodeState, number: &Value) -> Option<Block<H256>> {
+    let base_fee_per_gas = (!state.legacy).then(|| U256::from(BASE_FEE));
+    if state.chain.is_empty() {
+        return Some(Block {
+            number: Some(U64::from(1)),
+            base_fee_per_gas,
+            ..Default::default()
+        });
+    }
+
+    let number = match number.as_str().unwrap() {
+        "latest" | "pending" | "safe" | "finalized" => state.chain.len() - 1,
+        number => quantity(number) as usize,
+    };
+    state.chain.get(number).map(|block| Block {
+        number: Some(U64::from(number)),
+        hash: Some(block.hash),
+        base_fee_per_gas,
+        ..Default::default()
+    })
+}
+
+/// Logs of the blocks in the range of `filter`, emitted by its address with one of the event
+/// signatures of its first topic.
+fn logs(state: &NodeState, filter: &Value) -> Vec<Log> {
+    let from = quantity(filter["fromBlock"].as_str().unwrap());
+    let to = quantity(filter["toBlock"].as_str().unwrap());
+    let address: Address = serde_json::from_value(filter["address"].clone()).unwrap();
+    let signatures: Vec<H256> = serde_json::from_value(filter["topics"][0].clone()).unwrap();
+
+    let mut logs = Vec::new();
+    for number in from..=to {
+        let Some(block) = state.chain.get(number as usize) else {
+            continue;
+        };
+        for (index, log) in block.logs.iter().enumerate() {
+            if log.address != address || !signatures.contains(&log.topics[0]) {
+                continue;
+            }
+            logs.push(Log {
+                block_number: Some(U64::from(number)),
+                block_hash: Some(block.hash),
+                // One transaction per block.
+                transaction_hash: Some(H256::from_low_u64_be(number)),
+                log_index: Some(U256::from(index)),
+                removed: Some(false),
+                ..log.clone()
+            });
+        }
+    }
+    logs
+}
+
+/// A log of `event` emitted by `CONTRACT`.
+pub fn contract_log(event: &impl SolEvent) -> Log {
+    let data = event.encode_log_data();
+    Log {
+        address: CONTRACT.parse().unwrap(),
+        topics: data.topics().iter().map(|topic| H256(topic.0)).collect(),
+        data: data.data.to_vec().into(),
+        ..Default::default()
+    }
+}
+
+fn quantity(quantity: &str) -> u64 {
+    u64::from_str_radix(quantity.trim_start_matches("0x"), 16).unwrap()
+}
+
+fn find(transactions: &[Transaction], hash: &Value) -> Option<Transaction> {
+    let hash: H256 = serde_json::from_value(hash.clone()).unwrap();
+    transactions.iter().find(|tx| tx.hash == hash).cloned()
+}
+
+/// The error of a call reverting with `data`, as geth reports it.
+fn revert(request: &Value, data: &Bytes) -> Value {
+    json!({
+        "jsonrpc": "2.0",
+        "id": request["id"],
+        "error": { "code": 3, "message": "execution reverted", "data": data },
+    })
+}
+
+fn rpc_error(request: &Value, code: i64, message: &str) -> Value {
+    json!({
+        "jsonrpc": "2.0",
+        "id": request["id"],
+        "error": { "code": code, "message": message },
+    })
+}
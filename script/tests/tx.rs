@@ -0,0 +1,150 @@
+//! Sends transactions to a stand-in Ethereum node.
+
+mod node;
+
+use std::time::Duration;
+
+use ethers::types::{Address, Bytes, U256};
+use node::{MockNode, BASE_FEE, CHAIN_ID, CONTRACT, WALLET_KEY};
+use sp1_pay_script::tx::{TxError, TxSender};
+
+fn test_sender(node: &MockNode) -> TxSender {
+    TxSender::new(&node.start(), WALLET_KEY, CHAIN_ID)
+        .unwrap()
+        .with_poll_interval(Duration::from_millis(10))
+}
+
+fn contract() -> Address {
+    CONTRACT.parse().unwrap()
+}
+
+#[tokio::test]
+async fn test_transaction_is_sent_with_eip1559_fees() {
+    let node = MockNode::default();
+    let sender = test_sender(&node);
+
+    let hash = sender
+        .send(contract(), Bytes::from_static(&[0xde, 0xad]))
+        .await
+        .unwrap();
+    let receipt = sender.wait(hash).await.unwrap();
+    assert_eq!(receipt.transaction_hash, hash);
+
+    let mined = node.mined();
+    assert_eq!(mined.len(), 1);
+    let tx = &mined[0];
+    assert_eq!(tx.hash, hash);
+    assert_eq!(tx.from, sender.address());
+    assert_eq!(tx.to, Some(contract()));
+    assert_eq!(tx.input.as_ref(), &[0xde, 0xad]);
+    assert_eq!(tx.chain_id, Some(U256::from(CHAIN_ID)));
+    assert_eq!(tx.transaction_type.map(|ty| ty.as_u64()), Some(2));
+    assert!(tx.max_fee_per_gas.unwrap() > U256::from(BASE_FEE));
+    // The estimate plus its margin.
+    assert_eq!(tx.gas, U256::from(600_000));
+}
+
+#[tokio::test]
+async fn test_legacy_chain_uses_gas_price() {
+    let node = MockNode::default();
+    node.state().legacy = true;
+    let sender = test_sender(&node);
+
+    let hash = sender.send(contract(), Bytes::new()).await.unwrap();
+    sender.wait(hash).await.unwrap();
+
+    let tx = &node.mined()[0];
+    assert_eq!(tx.gas_price, Some(U256::from(BASE_FEE)));
+    assert!(tx.max_fee_per_gas.is_none());
+}
+
+#[tokio::test]
+async fn test_concurrent_sends_use_consecutive_nonces() {
+    let node = MockNode::default();
+    let sender = test_sender(&node);
+
+    let (a, b, c) = tokio::join!(
+        sender.send(contract(), Bytes::from_static(&[1])),
+        sender.send(contract(), Bytes::from_static(&[2])),
+        sender.send(contract(), Bytes::from_static(&[3])),
+    );
+    for hash in [a, b, c] {
+        sender.wait(hash.unwrap()).await.unwrap();
+    }
+
+    let nonces: Vec<_> = node.mined().iter().map(|tx| tx.nonce.as_u64()).collect();
+    assert_eq!(nonces, vec![0, 1, 2]);
+}
+
+#[tokio::test]
+async fn test_underpriced_transaction_is_sent_with_bumped_fees() {
+    let node = MockNode::default();
+    node.state()
+        .refusals
+        .push_back("replacement transaction underpriced".to_string());
+    let sender = test_sender(&node);
+
+    let hash = sender.send(contract(), Bytes::new()).await.unwrap();
+    sender.wait(hash).await.unwrap();
+
+    let received = node.state().received.clone();
+    assert_eq!(received.len(), 2);
+    assert_eq!(received[0].nonce, received[1].nonce);
+    let (refused, mined) = (&received[0], &received[1]);
+    assert_eq!(
+        mined.max_fee_per_gas.unwrap(),
+        refused.max_fee_per_gas.unwrap() * 115 / 100
+    );
+    assert_eq!(
+        mined.max_priority_fee_per_gas.unwrap(),
+        refused.max_priority_fee_per_gas.unwrap() * 115 / 100
+    );
+    assert_eq!(node.mined()[0].hash, hash);
+}
+
+#[tokio::test]
+async fn test_stale_nonce_is_read_again() {
+    let node = MockNode::default();
+    let url = node.start();
+    let sender = TxSender::new(&url, WALLET_KEY, CHAIN_ID).unwrap();
+    let other = TxSender::new(&url, WALLET_KEY, CHAIN_ID).unwrap();
+
+    sender.send(contract(), Bytes::new()).await.unwrap();
+    // Another process sending from the same wallet takes the nonce `sender` expects next.
+    other.send(contract(), Bytes::new()).await.unwrap();
+    sender.send(contract(), Bytes::new()).await.unwrap();
+
+    let nonces: Vec<_> = node.mined().iter().map(|tx| tx.nonce.as_u64()).collect();
+    assert_eq!(nonces, vec![0, 1, 2]);
+}
+
+#[tokio::test]
+async fn test_dropped_transaction_is_reported() {
+    let node = MockNode::default();
+    node.state().drops = 1;
+    let sender = test_sender(&node);
+
+    let dropped = sender.send(contract(), Bytes::new()).await.unwrap();
+    assert!(matches!(
+        sender.wait(dropped).await,
+        Err(TxError::Dropped(hash)) if hash == dropped
+    ));
+
+    // Sent again, it takes the nonce the dropped transaction left free.
+    let hash = sender.send(contract(), Bytes::new()).await.unwrap();
+    sender.wait(hash).await.unwrap();
+    assert_eq!(node.mined()[0].nonce, U256::zero());
+}
+
+#[tokio::test]
+async fn test_other_refusals_are_returned() {
+    let node = MockNode::default();
+    node.state()
+        .refusals
+        .push_back("insufficient funds for gas * price + value".to_string());
+    let sender = test_sender(&node);
+
+    let err = sender.send(contract(), Bytes::new()).await.unwrap_err();
+    assert!(err.to_string().contains("insufficient funds"), "{err:#}");
+    assert!(node.mined().is_empty());
+}
@@ -1,26 +1,21 @@
-use alloy_primitives::{U256, Bytes};
-use alloy_sol_types::sol;
+use std::{
+    sync::OnceLock,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use alloy_primitives::{B256, U256};
 use anyhow::Context;
+use common::{jwks::JwksClient, preflight::preflight};
+use dotenv::dotenv;
 use http_body_util::BodyExt;
-use serde::{Deserialize, Serialize};
+use serde::Deserialize;
 use serde_json::json;
-use sp1_sdk::{
-    proto::network::ProofMode, NetworkProver, SP1Stdin
-};
+use sp1_sdk::{proto::network::ProofMode, HashableKey, NetworkProver, SP1Stdin};
+use ui::{verifying_key, ClaimKey, ClaimNonce, ProofInputs, ValidationPolicy, ELF};
 use vercel_runtime::{run, Body, Error, Request, Response, StatusCode};
-use dotenv::dotenv;
-use reqwest::get;
-use std::str::FromStr;
-use serde_json::Value;
-use ui::ProofInputs;
-use ui::ProofOutputs;
-sol! {
-    interface IBonsaiPay {
-        function claim(bytes calldata proof, bytes calldata publicValues);
-    }
-}
 
-pub const ELF: &[u8] = include_bytes!("../../elf/riscv32im-succinct-zkvm-elf");
+/// Google's signing keys, reused by warm invocations for as long as Google allows.
+static GOOGLE_JWKS: OnceLock<JwksClient> = OnceLock::new();
 
 #[tokio::main]
 async fn main() -> Result<(), Error> {
@@ -31,6 +26,15 @@ async fn main() -> Result<(), Error> {
 #[derive(Deserialize)]
 struct TokenRequest {
     jwt: String,
+    /// Salt the depositor shared, for deposits to a salted claim ID.
+    #[serde(default)]
+    salt: Option<B256>,
+    /// What the deposit's claim ID is derived from: the email address as is, normalized or with
+    /// Gmail addresses folded, or the account's subject, see `ClaimKey`.
+    #[serde(default, rename = "claimKey")]
+    claim_key: ClaimKey,
+    /// Preimage of the nonce the token was requested with.
+    nonce: ClaimNonce,
 }
 
 pub async fn handler(req: Request) -> Result<Response<Body>, Error> {
@@ -40,7 +44,14 @@ pub async fn handler(req: Request) -> Result<Response<Body>, Error> {
     let token_request: TokenRequest = serde_json::from_slice(&bytes)
         .context("Failed to deserialize request body")?;
 
-    match request_proof(token_request.jwt).await {
+    match request_proof(
+        token_request.jwt,
+        token_request.claim_key,
+        token_request.salt,
+        token_request.nonce,
+    )
+    .await
+    {
         Ok(proof_id) => Ok(Response::builder()
             .status(StatusCode::OK)
             .header("Content-Type", "application/json")
@@ -52,18 +63,49 @@ pub async fn handler(req: Request) -> Result<Response<Body>, Error> {
     }
 }
 
-pub async fn request_proof(token: String) -> Result<String, Error> {
+/// Google tokens of the app's OAuth client, restricted to `GOOGLE_HOSTED_DOMAIN` if set.
+fn google_policy() -> Result<ValidationPolicy, Error> {
+    let policy = ValidationPolicy::google(std::env::var("VITE_GOOGLE_CLIENT_ID")?);
+    Ok(match std::env::var("GOOGLE_HOSTED_DOMAIN") {
+        Ok(domain) if !domain.is_empty() => policy.with_hosted_domain(domain),
+        _ => policy,
+    })
+}
+
+pub async fn request_proof(
+    token: String,
+    claim_key: ClaimKey,
+    salt: Option<B256>,
+    nonce: ClaimNonce,
+) -> Result<String, Error> {
     // Setup the logger.
     sp1_sdk::utils::setup_logger();
 
     // Setup the inputs.
     let mut stdin = SP1Stdin::new();
 
+    let jwks = GOOGLE_JWKS.get_or_init(JwksClient::google).fetch().await?;
     let inputs = ProofInputs {
         identity_provider: U256::ZERO,
         jwt: token,
-        cert: fetch_google_jwt_cert().await.unwrap(),
+        cert: jwks.raw.clone(),
+        timestamp: SystemTime::now().duration_since(UNIX_EPOCH)?.as_secs(),
+        policy: google_policy()?,
+        claim_key,
+        salt,
+        nonce,
     };
+
+    // Check that the contract at `VITE_CUSTODY_ADDRESS` would accept the proof before paying the
+    // network for it.
+    preflight(
+        &std::env::var("RPC_URL")?,
+        std::env::var("VITE_CUSTODY_ADDRESS")?.parse()?,
+        &inputs,
+        &jwks.keys,
+        &verifying_key().bytes32(),
+    )
+    .await?;
     stdin.write(&inputs);
 
     // Request the proof.
@@ -73,12 +115,3 @@ pub async fn request_proof(token: String) -> Result<String, Error> {
 
     Ok(proof_id)
 }
-
-pub async fn fetch_google_jwt_cert() -> Result<Bytes, Box<dyn std::error::Error>> {
-    let url = "https://www.googleapis.com/oauth2/v3/certs";
-    let response = get(url).await?.json::<serde_json::Value>().await?;
-    let bytes = Bytes::from(serde_json::to_vec(&response).unwrap());
-
-    Ok(bytes)
-}
-
@@ -1,18 +1,13 @@
-use alloy_primitives::{Address, B256};
+use std::{path::Path, time::Duration};
+
 use anyhow::Context;
+use dotenv::dotenv;
 use http_body_util::BodyExt;
-use serde::{Deserialize, Serialize};
-use serde_json::{json, Value };
-use sp1_sdk::{
-    NetworkProver, SP1ProofWithPublicValues, SP1VerifyingKey, HashableKey
-};
-use std::time::Duration;
+use serde::Deserialize;
+use serde_json::{json, Value};
+use sp1_sdk::{NetworkProver, SP1ProofWithPublicValues};
+use ui::{verifying_key, ProofFixture};
 use vercel_runtime::{run, Body, Error, Request, Response, StatusCode};
-use dotenv::dotenv;
-use std::path::PathBuf;
-use ui::ProofInputs;
-use ui::ProofOutputs;
-use alloy_sol_types::SolType;
 
 #[tokio::main]
 async fn main() -> Result<(), Error> {
@@ -49,7 +44,7 @@ pub async fn wait_proof(proof_id: &str) -> Result<Value, Error> {
     let proof = network_prover.wait_proof::<SP1ProofWithPublicValues>(proof_id, Some(Duration::from_secs(300))).await?;
     let proof_bytes = proof.bytes();
 
-    create_plonk_fixture(&proof);
+    create_plonk_fixture(&proof)?;
     
     Ok(json!({
         "proof": format!("0x{}", hex::encode(proof_bytes)),
@@ -58,46 +53,16 @@ pub async fn wait_proof(proof_id: &str) -> Result<Value, Error> {
     }))
 }
 
-/// A fixture that can be used to test the verification of SP1 zkVM proofs inside Solidity.
-#[derive(Debug, Clone, Serialize, Deserialize)]
-#[serde(rename_all = "camelCase")]
-struct SP1FibonacciProofFixture {
-    msg_sender: Address,
-    claim_id: B256,
-    public_values: String,
-    proof: String,
-}
+/// Writes a fixture of `proof` to `fixture.json`, to test its verification in Solidity.
+fn create_plonk_fixture(proof: &SP1ProofWithPublicValues) -> anyhow::Result<()> {
+    let fixture = ProofFixture::new(proof, verifying_key())?;
 
-/// Create a fixture for the given proof.
-fn create_plonk_fixture(proof: &SP1ProofWithPublicValues) {
-    // Deserialize the public values.
-    let bytes = proof.public_values.as_slice();
-    let (msg_sender, claim_id, _) = ProofOutputs::abi_decode(bytes, false).unwrap();
-
-    // Create the testing fixture so we can test things end-to-end.
-    let fixture = SP1FibonacciProofFixture {
-        msg_sender,
-        claim_id,
-        public_values: format!("0x{}", hex::encode(bytes)),
-        proof: format!("0x{}", hex::encode(proof.bytes())),
-    };
-
-    // The public values are the values whicha are publically commited to by the zkVM.
-    //
-    // If you need to expose the inputs or outputs of your program, you should commit them in
-    // the public values.
+    // The public values are the values which are publicly committed to by the zkVM.
     println!("Public Values: {}", fixture.public_values);
 
     // The proof proves to the verifier that the program was executed with some inputs that led to
-    // the give public values.
+    // the given public values.
     println!("Proof Bytes: {}", fixture.proof);
 
-    // Save the fixture to a file.
-    let fixture_path = PathBuf::from("./");
-    std::fs::create_dir_all(&fixture_path).expect("failed to create fixture path");
-    std::fs::write(
-        fixture_path.join("fixture.json"),
-        serde_json::to_string_pretty(&fixture).unwrap(),
-    )
-    .expect("failed to write fixture");
-}
\ No newline at end of file
+    fixture.save(Path::new("fixture.json"))
+}
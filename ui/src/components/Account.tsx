@@ -4,6 +4,7 @@ import tokens from "../assets/tokens.json";
 import { Balance } from "./Balance";
 import { Token } from "../libs/types";
 import { sha256 } from "@noble/hashes/sha256";
+import { normalizeIdentifier } from "../libs/utils";
 
 interface AccountProps {
   email: string | null;
@@ -14,7 +15,7 @@ interface AccountProps {
 const Account: React.FC<AccountProps> = (props) => {
   const { email, disabled, hideClaim } = props;
 
-  const claimId = toHex(sha256(email ?? ""));
+  const claimId = toHex(sha256(normalizeIdentifier(email ?? "")));
 
   return (
     <>
@@ -8,6 +8,7 @@ import { Token } from "../libs/types";
 import { parseEther, toHex} from "viem";
 import { toast } from "react-toastify";
 import { sha256 } from "@noble/hashes/sha256";
+import { normalizeIdentifier } from "../libs/utils";
 
 interface DepositProps {}
 
@@ -30,7 +31,7 @@ const Deposit: React.FC<DepositProps> = () => {
 
   const { data: txn, write: sendTxn } = useBonsaiPayDeposit({
     args: [
-      toHex(sha256(debouncedTo))
+      toHex(sha256(normalizeIdentifier(debouncedTo)))
     ] as never,
     value: parseEther(debouncedAmount) as never,
   });
@@ -2,6 +2,8 @@ import React, { useState, useCallback, useEffect } from "react";
 import Account from "./Account";
 import { useBonsaiPayClaimedEvent, useBonsaiPayBalanceOf } from "../generated";
 import { sha256 } from "@noble/hashes/sha256";
+import { normalizeIdentifier } from "../libs/utils";
+import { loadClaimNonce } from "../libs/nonce";
 import { toHex } from "viem";
 import { useContractWrite, usePrepareContractWrite } from "wagmi";
 import SP1PayABI from "../abi/SP1Pay";
@@ -53,7 +55,7 @@ const Prove: React.FC<ProveProps> = ({ disabled, email }) => {
   });
 
   const { data: balance } = useBonsaiPayBalanceOf({
-    args: [toHex(sha256(email ?? ""))],
+    args: [toHex(sha256(normalizeIdentifier(email ?? "")))],
   });
 
   useEffect(() => {
@@ -70,8 +72,10 @@ const Prove: React.FC<ProveProps> = ({ disabled, email }) => {
       .find((row) => row.startsWith("jwt="));
     const jwt = jwtCookie?.split("=")[1];
 
-    if (!jwt) {
-      console.error("JWT not found");
+    const nonce = loadClaimNonce();
+
+    if (!jwt || !nonce) {
+      console.error("JWT or nonce not found");
       setIsLoading(false);
       return;
     }
@@ -84,7 +88,8 @@ const Prove: React.FC<ProveProps> = ({ disabled, email }) => {
           "Content-Type": "application/json",
           "X-Auth-Token": jwt,
         },
-        body: JSON.stringify({ jwt }),
+        // Deposits made here are keyed on the normalized email address.
+        body: JSON.stringify({ jwt, nonce, claimKey: "NormalizedIdentifier" }),
       });
 
       if (requestResponse.ok) {
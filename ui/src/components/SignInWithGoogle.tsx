@@ -1,5 +1,10 @@
-import { useEffect } from "react";
-import { useAccount } from "wagmi";
+import { useEffect, useMemo } from "react";
+import { useAccount, useNetwork } from "wagmi";
+import {
+  claimNonceCommitment,
+  createClaimNonce,
+  storeClaimNonce,
+} from "../libs/nonce";
 
 declare global {
   interface Window {
@@ -9,8 +14,17 @@ declare global {
 }
 
 export const SignInWithGoogle = ({ disabled }) => {
-  const { VITE_GOOGLE_CLIENT_ID } = import.meta.env;
+  const { VITE_GOOGLE_CLIENT_ID, VITE_CUSTODY_ADDRESS } = import.meta.env;
   const { address } = useAccount();
+  const { chain } = useNetwork();
+
+  // Bind the token to this recipient, chain and contract. The preimage is kept for proving.
+  const nonce = useMemo(() => {
+    if (!address || !chain) return undefined;
+    const claimNonce = createClaimNonce(address, chain.id, VITE_CUSTODY_ADDRESS);
+    storeClaimNonce(claimNonce);
+    return claimNonceCommitment(claimNonce);
+  }, [address, chain, VITE_CUSTODY_ADDRESS]);
 
   useEffect(() => {
     // Ensure that the initGsi function is idempotent
@@ -26,7 +40,7 @@ export const SignInWithGoogle = ({ disabled }) => {
         window.google.accounts.id.initialize({
           client_id: VITE_GOOGLE_CLIENT_ID,
           callback: handleCredentialResponse,
-          nonce: address,
+          nonce,
           scope: "email",
           auto_select: false,
           state: "hello",
@@ -69,7 +83,7 @@ export const SignInWithGoogle = ({ disabled }) => {
       }
       initGsi();
     };
-  }, [VITE_GOOGLE_CLIENT_ID, address]);
+  }, [VITE_GOOGLE_CLIENT_ID, nonce]);
 
   const handleCredentialResponse = async (response) => {
     const { credential } = response;
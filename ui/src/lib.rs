@@ -1,15 +1,17 @@
-use alloy_primitives::{Address, Bytes, B256, U256};
-use serde::{Deserialize, Serialize};
-use alloy_sol_types::sol;
-use alloy_sol_types::SolType;
-
-#[derive(Serialize, Deserialize)]
-pub struct ProofInputs {
-    pub identity_provider: U256,
-    pub jwt: String,
-    pub cert: Bytes,
-}
+//! Types shared with the program. They come from `common`, so the handlers write the inputs and
+//! decode the outputs exactly as the program reads and commits them.
+
+use std::sync::OnceLock;
+
+pub use common::{
+    fixture::ProofFixture, ClaimKey, ClaimNonce, ProofInputs, ProofOutputs, ValidationPolicy,
+};
+use sp1_sdk::{ProverClient, SP1VerifyingKey};
 
-pub type ProofOutputs = sol! {
-    tuple(address, bytes32, bytes)
-};
\ No newline at end of file
+pub const ELF: &[u8] = include_bytes!("../../elf/riscv32im-succinct-zkvm-elf");
+
+/// The verifying key of `ELF`, set up by the first call of a warm invocation.
+pub fn verifying_key() -> &'static SP1VerifyingKey {
+    static VK: OnceLock<SP1VerifyingKey> = OnceLock::new();
+    VK.get_or_init(|| ProverClient::local().setup(ELF).1)
+}
@@ -0,0 +1,73 @@
+import {
+  encodeAbiParameters,
+  hexToBytes,
+  toHex,
+  zeroAddress,
+  type Address,
+  type Hex,
+} from "viem";
+import { sha256 } from "@noble/hashes/sha256";
+
+// Mirrors `common::ClaimNonce`, serialized as the proving API expects it.
+export type ClaimNonce = {
+  recipient: Address;
+  chainId: number;
+  contract: Address;
+  expiry: number;
+  random: Hex;
+  // Paid `fee` wei out of the claimed balance for relaying the claim.
+  relayer: Address;
+  fee: Hex;
+};
+
+const STORAGE_KEY = "claimNonce";
+const NONCE_TTL_SECONDS = 60 * 60;
+
+export const createClaimNonce = (
+  recipient: Address,
+  chainId: number,
+  contract: Address,
+  relayer: Address = zeroAddress,
+  fee: bigint = 0n
+): ClaimNonce => ({
+  recipient,
+  chainId,
+  contract,
+  expiry: Math.floor(Date.now() / 1000) + NONCE_TTL_SECONDS,
+  random: toHex(crypto.getRandomValues(new Uint8Array(32))),
+  relayer,
+  fee: toHex(fee),
+});
+
+// `sha256(abi.encode(recipient, chainId, contract, expiry, random, relayer, fee))`, the token's `nonce`.
+export const claimNonceCommitment = (nonce: ClaimNonce): Hex => {
+  const encoded = encodeAbiParameters(
+    [
+      { type: "address" },
+      { type: "uint64" },
+      { type: "address" },
+      { type: "uint64" },
+      { type: "bytes32" },
+      { type: "address" },
+      { type: "uint256" },
+    ],
+    [
+      nonce.recipient,
+      BigInt(nonce.chainId),
+      nonce.contract,
+      BigInt(nonce.expiry),
+      nonce.random,
+      nonce.relayer,
+      BigInt(nonce.fee),
+    ]
+  );
+  return toHex(sha256(hexToBytes(encoded)));
+};
+
+export const storeClaimNonce = (nonce: ClaimNonce) =>
+  sessionStorage.setItem(STORAGE_KEY, JSON.stringify(nonce));
+
+export const loadClaimNonce = (): ClaimNonce | null => {
+  const stored = sessionStorage.getItem(STORAGE_KEY);
+  return stored ? JSON.parse(stored) : null;
+};
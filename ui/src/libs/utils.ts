@@ -5,3 +5,8 @@ export const clearCookies = () => {
     document.cookie = name + "=;expires=Thu, 01 Jan 1970 00:00:00 GMT";
   });
 };
+
+// Lowercases ASCII letters only, matching `normalize_identifier` in `common`. Claim IDs of
+// normalized identifiers are proved with the `NormalizedIdentifier` claim key.
+export const normalizeIdentifier = (identifier: string) =>
+  identifier.replace(/[A-Z]/g, (c) => c.toLowerCase());